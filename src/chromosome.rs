@@ -1,4 +1,8 @@
 use crate::*;
+use std::fmt::Write;
+/// Представление Flex сети: (inp_links, neurons)
+pub type FlexNetView = (HashMap<usize, Vec<(usize, f32)>>, Vec<Vec<usize>>);
+
 #[derive(Clone, Debug)]
 pub struct Chromosome {
     ///состав структуры (bias or weight, layer_num, neuron_out, neuron_in)
@@ -26,11 +30,21 @@ impl Chromosome {
     pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut (f32, usize, usize, usize)> {
         self.genes.iter_mut()
     }
+    //отпечаток генов: по нему особь популяции узнается после оценки снаружи
+    //(родословная, AdaptiveOperators), даже если популяцию переупорядочили
+    pub(crate) fn fingerprint(&self) -> u64 {
+        use std::hash::{Hash, Hasher};
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        for (w, l, o, i) in self.iter() {
+            (w.to_bits(), l, o, i).hash(&mut hasher);
+        }
+        hasher.finish()
+    }
     /// Создание представления сети из весов (в них указана топология сети),
     /// без списка функций активации
     pub fn from_weights_to_flex_net_view(
         weights: impl IntoIterator<Item = (f32, usize, usize, usize)>
-    ) -> (HashMap<usize, Vec<(usize, f32)>>, Vec<Vec<usize>>) {
+    ) -> FlexNetView {
         //Список выходных нейронов (как ключи) со списками (номеров входных нейронов, весов) или
         //(0, смещение)
         let mut inp_links: HashMap<usize, Vec<(usize, f32)>> = HashMap::new();
@@ -38,7 +52,7 @@ impl Chromosome {
         let mut neurons: Vec<Vec<usize>> = Vec::new();
 
         for (weight, layer_num, neuron_out, neuron_in) in weights {
-            inp_links.entry(neuron_out).or_insert_with(Vec::new).push((neuron_in, weight));
+            inp_links.entry(neuron_out).or_default().push((neuron_in, weight));
 
             // Добавляем нейрон в соответствующий слой, если его еще нет
             if neurons.len() < layer_num {
@@ -61,7 +75,7 @@ impl Chromosome {
         let mut weights: Vec<(f32, usize, usize, usize)> = Vec::new();
         for (l_num, layer) in neurons.iter().enumerate() {//обход послойно
             for neuron_out in layer {//обход нейронов слоя
-                if let Some(neurons_in) = inp_links.get(&(*neuron_out)) {
+                if let Some(neurons_in) = inp_links.get(neuron_out) {
                     //обход входный связей и смещения нейрона
                     for (neuron_in, wt) in neurons_in {
                         weights.push((*wt, l_num+1, *neuron_out, *neuron_in));
@@ -73,6 +87,150 @@ impl Chromosome {
         self.genes.clear();
         self.genes.extend(&weights);
     }
+    /// Удаление "мертвой" структуры сети: скрытых нейронов, до которых нет пути ни от одного
+    /// входа или от которых нет пути до выходного слоя, вместе с их генами. Также удаляются
+    /// связи от нейронов, которых нет в сети (входы первого слоя - сенсоры, их связи остаются).
+    /// Входной и выходной слой не меняются, последний нейрон слоя не удаляется
+    /// (как и в Flex1Mutation).
+    pub fn prune(&mut self) -> PruneReport {
+        if !self.has_topology() {//нечего удалять
+            return PruneReport::default();
+        }
+        let (mut inp_links, mut neurons) =
+            Chromosome::from_weights_to_flex_net_view(self.iter());
+        if neurons.len() < 3 {//нет скрытых слоев
+            return PruneReport::default();
+        }
+        //все нейроны сети
+        let all: HashSet<usize> = neurons.iter().flatten().copied().collect();
+        //нейроны, до которых есть путь от входного слоя
+        let mut fed: HashSet<usize> = neurons[0].iter().copied().collect();
+        for layer in neurons.iter().skip(1) {
+            for n_out in layer {
+                if inp_links[n_out].iter().any(|(n_in, _)| *n_in != 0 && fed.contains(n_in)) {
+                    fed.insert(*n_out);
+                }
+            }
+        }
+        //нейроны, от которых есть путь до выходного слоя
+        let mut live: HashSet<usize> = neurons.last().unwrap().iter().copied().collect();
+        for layer in neurons.iter().skip(1).rev() {
+            for n_out in layer {
+                if live.contains(n_out) {
+                    live.extend(inp_links[n_out].iter().map(|(n_in, _)| *n_in));
+                }
+            }
+        }
+        //удаляем мертвые нейроны скрытых слоев
+        let mut removed_neurons: Vec<usize> = Vec::new();
+        let hidden = neurons.len() - 1;
+        for layer in &mut neurons[1..hidden] {
+            let dead: Vec<usize> = layer.iter()
+                .filter(|n| !fed.contains(n) || !live.contains(n))
+                .copied()
+                .collect();
+            for n in dead {
+                if layer.len() < 2 { break; }//последний нейрон в слое не трогаем
+                layer.retain(|x| *x != n);
+                inp_links.remove(&n);
+                removed_neurons.push(n);
+            }
+        }
+        //удаляем связи от удаленных и несуществующих нейронов. У входного слоя
+        //neuron_in - номер сенсора, а не нейрона, его связи не трогаем
+        let mut removed_links: usize = 0;
+        for n_out in neurons.iter().skip(1).flatten() {
+            let Some(links) = inp_links.get_mut(n_out) else { continue };
+            let len = links.len();
+            links.retain(|(n_in, _)| {
+                *n_in == 0 || (all.contains(n_in) && !removed_neurons.contains(n_in))
+            });
+            removed_links += len - links.len();
+        }
+        if removed_neurons.is_empty() && removed_links == 0 {
+            return PruneReport::default();//порядок генов не меняем
+        }
+        let len = self.len();
+        self.update_genes(inp_links, neurons);
+        removed_neurons.sort();
+        PruneReport {
+            removed_neurons,
+            removed_genes: len - self.len(),
+        }
+    }
+    /// Представление сети в формате Graphviz DOT: слои - ранги, нейроны - узлы с номером
+    /// и смещением, связи окрашены по знаку веса (синий +, красный -), толщина по модулю.
+    /// Отключенные входы (вес <= 0) рисуются пунктиром.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph chromosome {\n    rankdir=LR;\n");
+        if !self.has_topology() {
+            dot.push_str("}\n");
+            return dot;
+        }
+        let (inp_links, neurons) = Chromosome::from_weights_to_flex_net_view(self.iter());
+        //макс. модуль веса, для толщины связей
+        let max_weight = self.genes.iter()
+            .filter(|&&(_, layer_num, _, n_in)| layer_num > 1 && n_in != 0)
+            .map(|(w, _, _, _)| w.abs())
+            .fold(0.0f32, f32::max);
+        let last = neurons.len() - 1;
+        //нейроны послойно
+        for (l_num, layer) in neurons.iter().enumerate() {
+            let _ = writeln!(dot, "    subgraph layer_{} {{\n        rank=same;", l_num + 1);
+            for n_out in layer {
+                let links = &inp_links[n_out];
+                let bias = links.iter().find(|(n_in, _)| *n_in == 0).map_or(0.0, |(_, b)| *b);
+                let _ = if l_num == 0 {
+                    let enabled = links.iter().any(|(n_in, w)| n_in == n_out && *w > 0.0);
+                    writeln!(dot, "        n{n_out} [label=\"{n_out}\", shape=box{}];",
+                             if enabled { "" } else { ", style=dashed" })
+                } else {
+                    writeln!(dot, "        n{n_out} [label=\"{n_out}\\nb={bias:.3}\"{}];",
+                             if l_num == last { ", shape=doublecircle" } else { "" })
+                };
+            }
+            dot.push_str("    }\n");
+        }
+        //связи между нейронами
+        for layer in neurons.iter().skip(1) {
+            for n_out in layer {
+                for (n_in, w) in &inp_links[n_out] {
+                    if *n_in == 0 { continue; }//смещение показано в узле
+                    let color = if *w < 0.0 { "red" } else { "blue" };
+                    let width = if max_weight > 0.0 { 0.5 + 2.5 * w.abs() / max_weight } else { 1.0 };
+                    let _ = writeln!(dot, "    n{n_in} -> n{n_out} [color={color}, penwidth={width:.2}, \
+                                           tooltip=\"{w:.3}\"];");
+                }
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+    //есть ли в хромосоме топология сети (у генов-заглушек слой 0)
+    fn has_topology(&self) -> bool {
+        !self.is_empty() && !self.genes.iter().any(|&(_, layer_num, _, _)| layer_num == 0)
+    }
+}
+
+/// Отчет об удаленной структуре сети (Chromosome::prune)
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PruneReport {
+    removed_neurons: Vec<usize>,//номера удаленных нейронов, по возрастанию
+    removed_genes: usize,//сколько генов удалено (смещения, веса и связи)
+}
+
+impl PruneReport {
+    pub fn removed_neurons(&self) -> &[usize] {
+        &self.removed_neurons
+    }
+
+    pub fn removed_genes(&self) -> usize {
+        self.removed_genes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.removed_genes == 0
+    }
 }
 
 impl Index<usize> for Chromosome {
@@ -170,4 +328,91 @@ mod tests {
         assert_eq!(chromosome[1], (1.0, 0,0,0));
         assert_eq!(chromosome[2], (2.0, 0,0,0));
     }
+
+    mod prune {
+        use super::*;
+
+        #[test]
+        fn removes_dead_neurons_and_their_genes() {
+            let mut chromosome = Chromosome::new(vec![//(вес,слой,нейрон,вх.связь)
+                (0.0,1,1,0), (1.0,1,1,1), (0.0,1,2,0), (1.0,1,2,2),//1,2
+                (0.1,2,3,0), (0.2,2,3,1), (0.3,2,3,2),//3
+                (0.1,2,4,0), (0.2,2,4,1), (0.3,2,4,2),//4 - нет пути до выхода
+                (0.1,2,5,0), (0.2,2,5,9),//5 - нет пути от входов
+                (0.4,3,6,0), (0.5,3,6,3), (0.6,3,6,5),//6
+            ]);
+
+            let report = chromosome.prune();
+
+            assert_eq!(report.removed_neurons(), &[4, 5]);
+            assert_eq!(report.removed_genes(), 6);
+            assert_eq!(chromosome.len(), 9);
+            assert!(chromosome.iter().all(|(_, _, n_out, n_in)| {
+                ![4, 5].contains(&n_out) && ![4, 5, 9].contains(&n_in)
+            }));
+        }
+
+        #[test]
+        fn keeps_the_last_neuron_of_a_layer() {
+            let mut chromosome = Chromosome::new(vec![
+                (0.0,1,1,0), (1.0,1,1,1),//1
+                (0.1,2,2,0),//2 - нет пути от входов
+                (0.4,3,3,0), (0.5,3,3,2),//3
+            ]);
+
+            let report = chromosome.prune();
+
+            assert!(report.is_empty());
+            assert_eq!(chromosome.len(), 5);
+        }
+
+        #[test]
+        fn does_not_touch_a_healthy_network() {
+            let genes = vec![
+                (0.0,1,1,0), (1.0,1,1,1), (0.0,1,2,0), (1.0,1,2,2),//1,2
+                (0.1,2,3,0), (0.2,2,3,1), (0.3,2,3,2),//3
+                (0.4,3,4,0), (0.5,3,4,3),//4
+            ];
+            let mut chromosome = Chromosome::new(genes.clone());
+
+            assert!(chromosome.prune().is_empty());
+            assert_eq!(chromosome.iter().collect::<Vec<_>>(), genes);
+        }
+
+        #[test]
+        fn keeps_sensor_links() {
+            let mut chromosome = Chromosome::new(vec![
+                (0.0,1,1,0), (1.0,1,1,7), (0.0,1,2,0), (1.0,1,2,8),//1,2 - сенсоры 7 и 8
+                (0.1,2,3,0), (0.2,2,3,1), (0.3,2,3,2),//3
+                (0.1,2,4,0), (0.2,2,4,1),//4 - нет пути до выхода
+                (0.4,3,5,0), (0.5,3,5,3),//5
+            ]);
+
+            let report = chromosome.prune();
+
+            assert_eq!(report.removed_neurons(), &[4]);
+            assert_eq!(report.removed_genes(), 2);
+            assert!(chromosome.iter().any(|gene| gene == (1.0,1,1,7)));
+            assert!(chromosome.iter().any(|gene| gene == (1.0,1,2,8)));
+        }
+    }
+
+    #[test]
+    fn to_dot() {
+        let chromosome = Chromosome::new(vec![
+            (0.0,1,1,0), (1.0,1,1,1), (0.0,1,2,0), (0.0,1,2,2),//1, 2 - вход отключен
+            (0.1,2,3,0), (0.2,2,3,1), (-0.4,2,3,2),//3
+        ]);
+
+        let dot = chromosome.to_dot();
+
+        assert!(dot.starts_with("digraph chromosome {"));
+        assert!(dot.contains("rank=same;"));
+        assert!(dot.contains("n1 [label=\"1\", shape=box];"));
+        assert!(dot.contains("n2 [label=\"2\", shape=box, style=dashed];"));
+        assert!(dot.contains("n3 [label=\"3\\nb=0.100\", shape=doublecircle];"));
+        assert!(dot.contains("n1 -> n3 [color=blue, penwidth=1.75"));
+        assert!(dot.contains("n2 -> n3 [color=red, penwidth=3.00"));
+        assert_eq!(Chromosome::new(vec![]).to_dot(), "digraph chromosome {\n    rankdir=LR;\n}\n");
+    }
 }
@@ -0,0 +1,347 @@
+use crate::*;
+
+//Эволюционная стратегия с адаптацией ковариационной матрицы (CMA-ES) для хромосом
+//с неизменной топологией сети. Пространство поиска - веса и смещения хромосомы-шаблона,
+//кроме входов первого слоя (они, как и в мутациях, не меняются).
+//Между поколениями хранятся среднее, ковариационная матрица и размер шага.
+//Цикл: ask - создание популяции через Individual::create, оценка особей снаружи
+//(симуляция), tell - обновление распределения по fitness(). evolve = tell + ask.
+#[derive(Clone, Debug)]
+pub struct CmaEs {
+    template: Chromosome,//структура сети
+    genes: Vec<usize>,//индексы изменяемых генов шаблона
+    lambda: usize,//размер популяции
+    weights: Vec<f64>,//веса mu лучших особей при пересчете среднего
+    mueff: f64,
+    cc: f64,
+    cs: f64,
+    c1: f64,
+    cmu: f64,
+    damps: f64,
+    chi_n: f64,//ожидаемая длина вектора N(0,I)
+    mean: Vec<f64>,
+    sigma: f64,//размер шага
+    c: Vec<Vec<f64>>,//ковариационная матрица
+    pc: Vec<f64>,//путь эволюции для C
+    ps: Vec<f64>,//путь эволюции для sigma
+    b: Vec<Vec<f64>>,//собственные векторы C (по столбцам)
+    d: Vec<f64>,//корни собственных чисел C
+    generation: usize,
+    eigen_generation: usize,//поколение последнего разложения C
+}
+
+impl CmaEs {
+    /// template - хромосома с топологией сети и начальным средним, sigma - начальный шаг,
+    /// lambda - размер популяции (по умолчанию 4 + 3*ln(n))
+    pub fn new(template: &Chromosome, sigma: f32, lambda: Option<usize>) -> Self {
+        Self::try_new(template, sigma, lambda).unwrap_or_else(|err| panic!("{err}"))
+    }
+    /// new без паники: ошибка для sigma <= 0 и шаблона без изменяемых генов
+    pub fn try_new(template: &Chromosome, sigma: f32, lambda: Option<usize>) -> Result<Self, Error> {
+        if !(sigma > 0.0 && sigma.is_finite()) {
+            return Err(Error::InvalidParameter { name: "sigma", value: sigma });
+        }
+        let genes: Vec<usize> = template.iter()
+            .enumerate()
+            .filter(|(_, (_, layer_num, _, _))| *layer_num != 1)
+            .map(|(j, _)| j)
+            .collect();
+        let n = genes.len();
+        if n == 0 {
+            return Err(Error::InvalidParameter { name: "template", value: 0.0 });
+        }
+        let nf = n as f64;
+        let lambda = lambda.unwrap_or(4 + (3.0 * nf.ln()).floor() as usize).max(2);
+        let mu = lambda / 2;
+        let mut weights: Vec<f64> = (1..=mu)
+            .map(|i| (mu as f64 + 0.5).ln() - (i as f64).ln())
+            .collect();
+        let sum: f64 = weights.iter().sum();
+        weights.iter_mut().for_each(|w| *w /= sum);
+        let mueff = 1.0 / weights.iter().map(|w| w * w).sum::<f64>();
+        let cc = (4.0 + mueff / nf) / (nf + 4.0 + 2.0 * mueff / nf);
+        let cs = (mueff + 2.0) / (nf + mueff + 5.0);
+        let c1 = 2.0 / ((nf + 1.3).powi(2) + mueff);
+        let cmu = (1.0 - c1).min(2.0 * (mueff - 2.0 + 1.0 / mueff) / ((nf + 2.0).powi(2) + mueff));
+        let damps = 1.0 + 2.0 * (((mueff - 1.0) / (nf + 1.0)).sqrt() - 1.0).max(0.0) + cs;
+        let chi_n = nf.sqrt() * (1.0 - 1.0 / (4.0 * nf) + 1.0 / (21.0 * nf * nf));
+        let mean = genes.iter().map(|j| template[*j].0 as f64).collect();
+        Ok(Self {
+            template: template.clone(),
+            genes,
+            lambda,
+            weights,
+            mueff,
+            cc,
+            cs,
+            c1,
+            cmu,
+            damps,
+            chi_n,
+            mean,
+            sigma: sigma as f64,
+            c: identity(n),
+            pc: vec![0.0; n],
+            ps: vec![0.0; n],
+            b: identity(n),
+            d: vec![1.0; n],
+            generation: 0,
+            eigen_generation: 0,
+        })
+    }
+
+    pub fn lambda(&self) -> usize {
+        self.lambda
+    }
+
+    pub fn sigma(&self) -> f32 {
+        self.sigma as f32
+    }
+    /// Текущее среднее распределения в виде хромосомы
+    pub fn mean(&self) -> Chromosome {
+        self.to_chromosome(&self.mean)
+    }
+    /// Новая популяция из lambda особей: x = mean + sigma * B * D * z, z ~ N(0, I)
+    pub fn ask<I>(&self, rng: &mut dyn RngCore) -> Vec<I>
+    where
+        I: Individual,
+    {
+        let n = self.mean.len();
+        (0..self.lambda)
+            .map(|_| {
+                let dz: Vec<f64> = (0..n).map(|k| self.d[k] * standard_normal(rng)).collect();
+                let x: Vec<f64> = (0..n)
+                    .map(|r| self.mean[r] + self.sigma * (0..n).map(|k| self.b[r][k] * dz[k]).sum::<f64>())
+                    .collect();
+                I::create(self.to_chromosome(&x))
+            })
+            .collect()
+    }
+    /// Обновление распределения по оцененной популяции, возвращает ее статистику
+    pub fn tell<I>(&mut self, population: &[I]) -> Statistics
+    where
+        I: Individual,
+    {
+        self.try_tell(population).unwrap_or_else(|err| panic!("{err}"))
+    }
+    /// tell без паники: для обновления нужны хотя бы 2 особи
+    #[allow(clippy::needless_range_loop)] // k - номер веса во всех векторах
+    pub fn try_tell<I>(&mut self, population: &[I]) -> Result<Statistics, Error>
+    where
+        I: Individual,
+    {
+        if population.is_empty() {
+            return Err(Error::EmptyPopulation);
+        }
+        if population.len() < 2 {
+            return Err(Error::InvalidParameter { name: "population", value: population.len() as f32 });
+        }
+        let n = self.mean.len();
+        let nf = n as f64;
+        //особи по убыванию fitness, mu лучших
+        let mut order: Vec<usize> = (0..population.len()).collect();
+        order.sort_by(|a, b| {
+            population[*b].fitness().partial_cmp(&population[*a].fitness()).unwrap_or(Ordering::Equal)
+        });
+        let mu = self.weights.len().min(population.len());
+        let weights: Vec<f64> = {
+            let sum: f64 = self.weights[..mu].iter().sum();
+            self.weights[..mu].iter().map(|w| w / sum).collect()
+        };
+        //y = (x - mean) / sigma лучших особей
+        let ys: Vec<Vec<f64>> = order[..mu].iter()
+            .map(|j| {
+                let x = self.weights_of(population[*j].chromosome());
+                x.iter().zip(&self.mean).map(|(x, m)| (x - m) / self.sigma).collect()
+            })
+            .collect();
+        let y_w: Vec<f64> = (0..n)
+            .map(|k| ys.iter().zip(&weights).map(|(y, w)| w * y[k]).sum())
+            .collect();
+        for k in 0..n {
+            self.mean[k] += self.sigma * y_w[k];
+        }
+        //C^(-1/2) * y_w = B * D^-1 * B^T * y_w
+        let bt_y: Vec<f64> = (0..n)
+            .map(|k| (0..n).map(|r| self.b[r][k] * y_w[r]).sum::<f64>() / self.d[k])
+            .collect();
+        let c_inv_sqrt_y: Vec<f64> = (0..n)
+            .map(|r| (0..n).map(|k| self.b[r][k] * bt_y[k]).sum())
+            .collect();
+        let ps_coeff = (self.cs * (2.0 - self.cs) * self.mueff).sqrt();
+        for k in 0..n {
+            self.ps[k] = (1.0 - self.cs) * self.ps[k] + ps_coeff * c_inv_sqrt_y[k];
+        }
+        self.generation += 1;
+        let ps_norm = self.ps.iter().map(|p| p * p).sum::<f64>().sqrt();
+        let hsig = ps_norm / (1.0 - (1.0 - self.cs).powi(2 * self.generation as i32)).sqrt() / self.chi_n
+            < 1.4 + 2.0 / (nf + 1.0);
+        let pc_coeff = (self.cc * (2.0 - self.cc) * self.mueff).sqrt();
+        for k in 0..n {
+            self.pc[k] = (1.0 - self.cc) * self.pc[k] + if hsig { pc_coeff * y_w[k] } else { 0.0 };
+        }
+        //адаптация ковариационной матрицы: rank-one и rank-mu
+        let delta_hsig = if hsig { 0.0 } else { self.cc * (2.0 - self.cc) };
+        for r in 0..n {
+            for k in 0..n {
+                let rank_mu: f64 = ys.iter().zip(&weights).map(|(y, w)| w * y[r] * y[k]).sum();
+                self.c[r][k] = (1.0 - self.c1 - self.cmu) * self.c[r][k]
+                    + self.c1 * (self.pc[r] * self.pc[k] + delta_hsig * self.c[r][k])
+                    + self.cmu * rank_mu;
+            }
+        }
+        //адаптация размера шага
+        self.sigma *= ((self.cs / self.damps) * (ps_norm / self.chi_n - 1.0)).exp();
+        //разложение C не на каждом поколении, оно дорогое
+        let lazy = (self.lambda as f64 / (self.c1 + self.cmu) / nf / 10.0).max(1.0);
+        if (self.generation - self.eigen_generation) as f64 >= lazy {
+            self.eigen_generation = self.generation;
+            let (values, vectors) = symmetric_eigen(&self.c);
+            self.d = values.iter().map(|v| v.max(1e-20).sqrt()).collect();
+            self.b = vectors;
+        }
+        let mut statistic = Statistics::new(population);
+        statistic.set_changed_count(population.len());
+        Ok(statistic)
+    }
+    /// 1 шаг: tell по оцененной популяции и ask новой, как GeneticAlgorithm::evolve
+    pub fn evolve<I>(&mut self, rng: &mut dyn RngCore, population: &[I]) -> (Vec<I>, Statistics)
+    where
+        I: Individual,
+    {
+        self.try_evolve(rng, population).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    pub fn try_evolve<I>(&mut self, rng: &mut dyn RngCore, population: &[I]) -> Result<(Vec<I>, Statistics), Error>
+    where
+        I: Individual,
+    {
+        let statistic = self.try_tell(population)?;
+        Ok((self.ask(rng), statistic))
+    }
+    //веса изменяемых генов в хромосому по шаблону
+    fn to_chromosome(&self, x: &[f64]) -> Chromosome {
+        let mut genes: Vec<(f32, usize, usize, usize)> = self.template.iter().collect();
+        for (k, j) in self.genes.iter().enumerate() {
+            genes[*j].0 = x[k] as f32;
+        }
+        Chromosome::new(genes)
+    }
+    //веса изменяемых генов хромосомы, выровненной с шаблоном (align), гены без пары
+    //берутся из среднего
+    fn weights_of(&self, chromosome: &Chromosome) -> Vec<f64> {
+        let pairs = align(&self.template, chromosome);
+        self.genes.iter()
+            .zip(&self.mean)
+            .map(|(j, m)| pairs[*j].map_or(*m, |k| chromosome[k].0 as f64))
+            .collect()
+    }
+}
+
+fn identity(n: usize) -> Vec<Vec<f64>> {
+    (0..n).map(|r| (0..n).map(|k| if r == k { 1.0 } else { 0.0 }).collect()).collect()
+}
+
+//нормальное распределение N(0, 1) (преобразование Бокса-Мюллера)
+fn standard_normal(rng: &mut dyn RngCore) -> f64 {
+    let u1: f64 = 1.0 - rng.gen::<f64>();//(0, 1]
+    let u2: f64 = rng.gen();
+    (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+}
+
+//Собственные числа и векторы (по столбцам) симметричной матрицы, метод Якоби
+#[allow(clippy::needless_range_loop)] // индексы строк и столбцов матриц
+fn symmetric_eigen(matrix: &[Vec<f64>]) -> (Vec<f64>, Vec<Vec<f64>>) {
+    let n = matrix.len();
+    let mut a: Vec<Vec<f64>> = matrix.to_vec();
+    let mut v = identity(n);
+    for _ in 0..100 {
+        let off: f64 = (0..n)
+            .flat_map(|p| (p + 1..n).map(move |q| (p, q)))
+            .map(|(p, q)| a[p][q] * a[p][q])
+            .sum();
+        if off < 1e-22 { break; }
+        for p in 0..n {
+            for q in p + 1..n {
+                if a[p][q].abs() < 1e-30 { continue; }
+                let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let t = if theta == 0.0 { 1.0 } else { t };
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+                for k in 0..n {
+                    let (akp, akq) = (a[k][p], a[k][q]);
+                    a[k][p] = c * akp - s * akq;
+                    a[k][q] = s * akp + c * akq;
+                }
+                for k in 0..n {
+                    let (apk, aqk) = (a[p][k], a[q][k]);
+                    a[p][k] = c * apk - s * aqk;
+                    a[q][k] = s * apk + c * aqk;
+                }
+                for k in 0..n {
+                    let (vkp, vkq) = (v[k][p], v[k][q]);
+                    v[k][p] = c * vkp - s * vkq;
+                    v[k][q] = s * vkp + c * vkq;
+                }
+            }
+        }
+    }
+    ((0..n).map(|k| a[k][k]).collect(), v)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn eigen() {
+        let (values, vectors) = symmetric_eigen(&[vec![2.0, 1.0], vec![1.0, 2.0]]);
+        let mut sorted = values.clone();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        approx::assert_relative_eq!(sorted.as_slice(), [1.0, 3.0].as_slice(), epsilon = 1e-9);
+        //A * v = lambda * v
+        for k in 0..2 {
+            let av0 = 2.0 * vectors[0][k] + vectors[1][k];
+            approx::assert_relative_eq!(av0, values[k] * vectors[0][k], epsilon = 1e-9);
+        }
+    }
+
+    #[test]
+    fn weights_by_position() {
+        //у фиктивных генов слоя 0 один ключ, веса берутся по позиции
+        let template: Chromosome = vec![(0.0,0,0,0), (0.0,0,0,0), (0.0,0,0,0)].into_iter().collect();
+        let cma = CmaEs::new(&template, 0.5, None);
+        let chromosome: Chromosome = vec![(1.0,0,0,0), (2.0,0,0,0), (3.0,0,0,0)].into_iter().collect();
+
+        assert_eq!(cma.weights_of(&chromosome), vec![1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn improves_fitness() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let template: Chromosome = vec![(1.0,1,1,1), (0.0,2,2,0), (0.0,2,2,1), (0.0,2,3,0)]
+            .into_iter()
+            .collect();
+        let mut cma = CmaEs::new(&template, 0.5, Some(8));
+
+        let mut population: Vec<TestIndividual> = cma.ask(&mut rng);
+        assert_eq!(population.len(), 8);
+        let first = Statistics::new(&population);
+        let mut last = first.clone();
+        for _ in 0..30 {
+            let (new_population, statistic) = cma.evolve(&mut rng, &population);
+            population = new_population;
+            last = statistic;
+        }
+
+        //fitness TestIndividual - сумма весов, у максимума нет предела
+        assert!(last.max_fitness() > first.max_fitness() + 3.0);
+        assert!(cma.mean().iter().skip(1).all(|(w, _, _, _)| w > 0.5));
+        //входы первого слоя не меняются
+        assert!(population.iter().all(|i| i.chromosome()[0] == (1.0, 1, 1, 1)));
+    }
+}
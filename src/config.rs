@@ -0,0 +1,412 @@
+use crate::*;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+//Декларативная конфигурация алгоритма: методы селекции, кроссовера и мутации с параметрами,
+//размер популяции, seed и условия остановки. С feature "toml" или "json" читается из файла,
+//методы в нем задаются полем method, например:
+//  population_size = 50
+//  seed = 7
+//  [selection]
+//  method = "roulette_wheel"
+//  [crossover]
+//  method = "blend"
+//  alpha = 0.5
+//  [mutation]
+//  method = "gaussian"
+//  chance = 0.01
+//  coeff = 0.3
+//  [termination]
+//  max_generations = 1000
+//Алгоритм собирает build() (GeneticAlgorithm) или build_flex() (GeneticFlexAlgorithm)
+//как Box<dyn Evolve<I>>, параметры проверяются там же без паники (try_new методов).
+//Собранный алгоритм принимает только популяции размера population_size.
+
+/// Конфигурация алгоритма
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AlgorithmConfig {
+    pub population_size: usize,//размер популяции, другой размер - ошибка try_evolve
+    pub seed: Option<u64>,//без seed генератор инициализируется случайно
+    pub selection: SelectionConfig,
+    pub scaling: Option<ScalingConfig>,//масштабирование fitness перед селекцией
+    pub crossover: CrossoverConfig,
+    pub mutation: MutationConfig,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub elitism: usize,//только для build
+    pub sim_generation_length: Option<usize>,//только для build_flex
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub termination: TerminationConfig,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "method", rename_all = "snake_case"))]
+pub enum SelectionConfig {
+    RouletteWheel,
+    Nsga2,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "method", rename_all = "snake_case"))]
+pub enum ScalingConfig {
+    Linear { c: f32 },
+    SigmaTruncation { c: f32 },
+    PowerLaw { k: f32 },
+    Window { window: usize },
+    Rank,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "method", rename_all = "snake_case"))]
+pub enum CrossoverConfig {
+    Uniform,
+    Neat,
+    Arithmetic { alpha: f32 },
+    Blend { alpha: f32 },
+    Sbx { eta: f32 },
+    Neuron,
+    Layer,
+    SinglePoint,
+    MultiPoint { points: usize },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "method", rename_all = "snake_case"))]
+pub enum MutationConfig {
+    Gaussian { chance: f32, coeff: f32 },//для build
+    Flex1 { chance: f32, coeff: f32, eye_cells: usize },//для build_flex
+}
+
+/// Условия остановки обучения, достаточно любого из заданных
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TerminationConfig {
+    pub max_generations: Option<usize>,
+    pub target_fitness: Option<f32>,//лучший fitness, при котором цель достигнута
+    pub max_stagnation: Option<usize>,//поколений без улучшения лучшего fitness
+}
+
+impl TerminationConfig {
+    /// Пора ли остановить обучение по истории статистики
+    pub fn is_done(&self, history: &StatisticsHistory) -> bool {
+        let Some(last) = history.records().last() else {
+            return false;
+        };
+        if self.max_generations.is_some_and(|max| history.len() >= max) {
+            return true;
+        }
+        let best = history.best_fitness().unwrap_or(f32::NEG_INFINITY);
+        if self.target_fitness.is_some_and(|target| best >= target) {
+            return true;
+        }
+        let best_generation = history.best_generation().unwrap_or(last.generation());
+        self.max_stagnation
+            .is_some_and(|max| last.generation().saturating_sub(best_generation) >= max)
+    }
+}
+
+impl AlgorithmConfig {
+    #[cfg(feature = "toml")]
+    pub fn from_toml_str(s: &str) -> Result<Self, Error> {
+        toml::from_str(s).map_err(|err| Error::Config(err.to_string()))
+    }
+
+    #[cfg(feature = "json")]
+    pub fn from_json_str(s: &str) -> Result<Self, Error> {
+        serde_json::from_str(s).map_err(|err| Error::Config(err.to_string()))
+    }
+    /// Генератор случайных чисел по seed конфигурации
+    pub fn rng(&self) -> ChaCha8Rng {
+        match self.seed {
+            Some(seed) => ChaCha8Rng::seed_from_u64(seed),
+            None => ChaCha8Rng::from_entropy(),
+        }
+    }
+    /// GeneticAlgorithm по конфигурации, мутация должна быть gaussian
+    pub fn build<I>(&self) -> Result<Box<dyn Evolve<I>>, Error>
+    where
+        I: Individual,
+    {
+        self.check_population()?;
+        if self.elitism > self.population_size {
+            return Err(Error::InvalidParameter { name: "elitism", value: self.elitism as f32 });
+        }
+        if let Some(sim_generation_length) = self.sim_generation_length {
+            return Err(Error::InvalidParameter {
+                name: "sim_generation_length",
+                value: sim_generation_length as f32,
+            });
+        }
+        let mutation_method = match self.mutation {
+            MutationConfig::Gaussian { chance, coeff } => GaussianMutation::try_new(chance, coeff)?,
+            MutationConfig::Flex1 { .. } =>
+                return Err(Error::Config("flex1 mutation requires build_flex".to_string())),
+        };
+        let crossover_method = self.crossover_method()?;
+        let mutation_method: Box<dyn MutationMethod> = Box::new(mutation_method);
+        //у каждой селекции свой тип алгоритма
+        Ok(match (&self.selection, self.scaling()?) {
+            (SelectionConfig::RouletteWheel, None) =>
+                genetic(self, RouletteWheelSelection, crossover_method, mutation_method),
+            (SelectionConfig::RouletteWheel, Some(scaling)) =>
+                genetic(self, ScaledSelection::new(RouletteWheelSelection, scaling),
+                        crossover_method, mutation_method),
+            (SelectionConfig::Nsga2, None) =>
+                genetic(self, Nsga2Selection::new(), crossover_method, mutation_method),
+            //NSGA-II выбирает по fitness_vector, масштабировать нечего
+            (SelectionConfig::Nsga2, Some(_)) =>
+                return Err(Error::Config("nsga2 selection does not use scaling".to_string())),
+        })
+    }
+    /// GeneticFlexAlgorithm по конфигурации, мутация должна быть flex1
+    pub fn build_flex<I>(&self) -> Result<Box<dyn Evolve<I>>, Error>
+    where
+        I: IndividualFlex + Clone,
+    {
+        self.check_population()?;
+        //во Flex режиме особи живут по life_time, элитизма нет
+        if self.elitism > 0 {
+            return Err(Error::InvalidParameter { name: "elitism", value: self.elitism as f32 });
+        }
+        let sim_generation_length = self.sim_generation_length.ok_or(Error::InvalidParameter {
+            name: "sim_generation_length",
+            value: 0.0,
+        })?;
+        let mutation_method = match self.mutation {
+            MutationConfig::Flex1 { chance, coeff, eye_cells } =>
+                Flex1Mutation::try_new(chance, coeff, eye_cells)?,
+            MutationConfig::Gaussian { .. } =>
+                return Err(Error::Config("gaussian mutation requires build".to_string())),
+        };
+        let crossover_method = self.crossover_method()?;
+        match (&self.selection, self.scaling()?) {
+            (SelectionConfig::RouletteWheel, None) =>
+                genetic_flex(self, sim_generation_length, RouletteWheelSelection, crossover_method, mutation_method),
+            (SelectionConfig::RouletteWheel, Some(scaling)) =>
+                genetic_flex(self, sim_generation_length, ScaledSelection::new(RouletteWheelSelection, scaling),
+                             crossover_method, mutation_method),
+            (SelectionConfig::Nsga2, None) =>
+                genetic_flex(self, sim_generation_length, Nsga2Selection::new(), crossover_method, mutation_method),
+            (SelectionConfig::Nsga2, Some(_)) =>
+                Err(Error::Config("nsga2 selection does not use scaling".to_string())),
+        }
+    }
+
+    fn check_population(&self) -> Result<(), Error> {
+        if self.population_size == 0 {
+            return Err(Error::InvalidParameter { name: "population_size", value: 0.0 });
+        }
+        Ok(())
+    }
+
+    fn scaling(&self) -> Result<Option<Box<dyn FitnessScaling>>, Error> {
+        let scaling: Box<dyn FitnessScaling> = match self.scaling {
+            None => return Ok(None),
+            Some(ScalingConfig::Linear { c }) => Box::new(LinearScaling::try_new(c)?),
+            Some(ScalingConfig::SigmaTruncation { c }) => Box::new(SigmaTruncation::try_new(c)?),
+            Some(ScalingConfig::PowerLaw { k }) => Box::new(PowerLawScaling::try_new(k)?),
+            Some(ScalingConfig::Window { window }) => Box::new(WindowScaling::try_new(window)?),
+            Some(ScalingConfig::Rank) => Box::new(RankScaling),
+        };
+        Ok(Some(scaling))
+    }
+
+    fn crossover_method(&self) -> Result<Box<dyn CrossoverMethod>, Error> {
+        Ok(match self.crossover {
+            CrossoverConfig::Uniform => Box::new(UniformCrossover),
+            CrossoverConfig::Neat => Box::new(NeatCrossover),
+            CrossoverConfig::Arithmetic { alpha } => Box::new(ArithmeticCrossover::try_new(alpha)?),
+            CrossoverConfig::Blend { alpha } => Box::new(BlendCrossover::try_new(alpha)?),
+            CrossoverConfig::Sbx { eta } => Box::new(SimulatedBinaryCrossover::try_new(eta)?),
+            CrossoverConfig::Neuron => Box::new(NeuronCrossover),
+            CrossoverConfig::Layer => Box::new(LayerCrossover),
+            CrossoverConfig::SinglePoint => Box::new(SinglePointCrossover),
+            CrossoverConfig::MultiPoint { points } => Box::new(MultiPointCrossover::try_new(points)?),
+        })
+    }
+}
+
+//алгоритм, принимающий популяции только размера population_size
+struct PopulationSize<E> {
+    algorithm: E,
+    population_size: usize,
+}
+
+impl<E, I> Evolve<I> for PopulationSize<E>
+where
+    E: Evolve<I>,
+{
+    fn try_evolve(&self, rng: &mut dyn RngCore, population: &[I]) -> Result<(Vec<I>, Statistics), Error> {
+        if population.len() != self.population_size {
+            return Err(Error::InvalidParameter { name: "population", value: population.len() as f32 });
+        }
+        self.algorithm.try_evolve(rng, population)
+    }
+}
+
+//алгоритмы с селекцией конкретного типа
+fn genetic<S, I>(
+    config: &AlgorithmConfig,
+    selection_method: S,
+    crossover_method: Box<dyn CrossoverMethod>,
+    mutation_method: Box<dyn MutationMethod>,
+) -> Box<dyn Evolve<I>>
+where
+    S: SelectionMethod + 'static,
+    I: Individual,
+{
+    Box::new(PopulationSize {
+        algorithm: GeneticAlgorithm::from_parts(selection_method, crossover_method, mutation_method)
+            .with_elitism(config.elitism),
+        population_size: config.population_size,
+    })
+}
+
+fn genetic_flex<S, I>(
+    config: &AlgorithmConfig,
+    sim_generation_length: usize,
+    selection_method: S,
+    crossover_method: Box<dyn CrossoverMethod>,
+    mutation_method: Flex1Mutation,
+) -> Result<Box<dyn Evolve<I>>, Error>
+where
+    S: SelectionMethod + 'static,
+    I: IndividualFlex + Clone,
+{
+    Ok(Box::new(PopulationSize {
+        algorithm: GeneticFlexAlgorithm::try_from_parts(
+            sim_generation_length, selection_method, crossover_method, mutation_method)?,
+        population_size: config.population_size,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> AlgorithmConfig {
+        AlgorithmConfig {
+            population_size: 4,
+            seed: Some(7),
+            selection: SelectionConfig::RouletteWheel,
+            scaling: Some(ScalingConfig::Rank),
+            crossover: CrossoverConfig::Blend { alpha: 0.5 },
+            mutation: MutationConfig::Gaussian { chance: 0.5, coeff: 0.3 },
+            elitism: 1,
+            sim_generation_length: None,
+            termination: TerminationConfig { max_generations: Some(3), ..Default::default() },
+        }
+    }
+
+    fn individual(weights: &[f32]) -> TestIndividual {
+        TestIndividual::create(weights.iter().enumerate().map(|(n, w)| (*w, 2, n + 1, 0)).collect())
+    }
+
+    #[test]
+    fn build_and_run() {
+        let config = config();
+        let ga = config.build::<TestIndividual>().unwrap();
+        let mut rng = config.rng();
+        let mut population = vec![
+            individual(&[1.0, 0.0]),
+            individual(&[4.0, 1.0]),
+            individual(&[0.5, 0.5]),
+            individual(&[3.0, 3.0]),
+        ];
+        let mut history = StatisticsHistory::new();
+        while !config.termination.is_done(&history) {
+            let (new_population, statistic) = ga.evolve(&mut rng, &population);
+            population = new_population;
+            history.record(statistic);
+        }
+        assert_eq!(history.len(), 3);
+        assert_eq!(population.len(), 4);
+        //популяция не того размера
+        assert_eq!(ga.try_evolve(&mut rng, &population[..3]).err(),
+                   Some(Error::InvalidParameter { name: "population", value: 3.0 }));
+    }
+
+    #[test]
+    fn validation() {
+        let mut config = config();
+        config.crossover = CrossoverConfig::Arithmetic { alpha: 2.0 };
+        assert_eq!(config.build::<TestIndividual>().err(),
+                   Some(Error::InvalidParameter { name: "alpha", value: 2.0 }));
+
+        let mut config = self::config();
+        config.population_size = 0;
+        assert!(config.build::<TestIndividual>().is_err());
+
+        let mut config = self::config();
+        config.mutation = MutationConfig::Flex1 { chance: 0.5, coeff: 0.5, eye_cells: 9 };
+        assert!(matches!(config.build::<TestIndividual>().err(), Some(Error::Config(_))));
+        assert!(config.build_flex::<FlexIndividual>().is_err());//элитизм и нет sim_generation_length
+        config.elitism = 0;
+        config.sim_generation_length = Some(2500);
+        assert!(config.build_flex::<FlexIndividual>().is_ok());
+        //NSGA-II не масштабирует fitness
+        config.selection = SelectionConfig::Nsga2;
+        assert!(matches!(config.build_flex::<FlexIndividual>().err(), Some(Error::Config(_))));
+
+        let mut config = self::config();
+        config.selection = SelectionConfig::Nsga2;
+        assert!(matches!(config.build::<TestIndividual>().err(), Some(Error::Config(_))));
+    }
+
+    #[test]
+    fn termination() {
+        let termination = TerminationConfig { target_fitness: Some(5.0), max_stagnation: Some(2), ..Default::default() };
+        let population = [individual(&[1.0]), individual(&[2.0])];
+        let mut history = StatisticsHistory::new();
+        assert!(!termination.is_done(&history));
+        history.record(Statistics::new(&population));
+        history.record(Statistics::new(&population));
+        assert!(!termination.is_done(&history));
+        history.record(Statistics::new(&population));//2 поколения без улучшения
+        assert!(termination.is_done(&history));
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn from_toml() {
+        let config = AlgorithmConfig::from_toml_str(r#"
+            population_size = 4
+            seed = 7
+            elitism = 1
+            [selection]
+            method = "roulette_wheel"
+            [scaling]
+            method = "rank"
+            [crossover]
+            method = "blend"
+            alpha = 0.5
+            [mutation]
+            method = "gaussian"
+            chance = 0.5
+            coeff = 0.3
+            [termination]
+            max_generations = 3
+        "#).unwrap();
+        assert_eq!(config, self::config());
+        assert!(matches!(AlgorithmConfig::from_toml_str("population_size = 4"), Err(Error::Config(_))));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn from_json() {
+        let config = AlgorithmConfig::from_json_str(r#"{
+            "population_size": 4,
+            "seed": 7,
+            "elitism": 1,
+            "selection": { "method": "roulette_wheel" },
+            "scaling": { "method": "rank" },
+            "crossover": { "method": "blend", "alpha": 0.5 },
+            "mutation": { "method": "gaussian", "chance": 0.5, "coeff": 0.3 },
+            "termination": { "max_generations": 3 }
+        }"#).unwrap();
+        assert_eq!(config, self::config());
+    }
+}
@@ -1,7 +1,21 @@
 //Реализованные модули алгоритмов кроссовера
 mod uniform;
+mod neat;
+mod arithmetic;
+mod blend;
+mod sbx;
+mod neuron;
+mod layer;
+mod n_point;
 //Экспорт алгоритмов
 pub use self::uniform::*;
+pub use self::neat::*;
+pub use self::arithmetic::*;
+pub use self::blend::*;
+pub use self::sbx::*;
+pub use self::neuron::*;
+pub use self::layer::*;
+pub use self::n_point::*;
 
 use crate::*;
 
@@ -12,4 +26,282 @@ pub trait CrossoverMethod {
         parent_a: &Chromosome,
         parent_b: &Chromosome,
     ) -> Chromosome;
+    //кроссовер с учетом пригодности родителей, по умолчанию она не используется
+    fn crossover_with_fitness(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome,
+        _fitness_a: f32,
+        parent_b: &Chromosome,
+        _fitness_b: f32,
+    ) -> Chromosome {
+        self.crossover(rng, parent_a, parent_b)
+    }
+    //два ребенка от пары родителей. По умолчанию - два независимых кроссовера,
+    //методы, которые это умеют, возвращают двух дополняющих друг друга детей
+    fn crossover_pair(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome,
+        parent_b: &Chromosome,
+    ) -> (Chromosome, Chromosome) {
+        (self.crossover(rng, parent_a, parent_b), self.crossover(rng, parent_b, parent_a))
+    }
+    //два ребенка с учетом пригодности родителей, по умолчанию она не используется
+    fn crossover_pair_with_fitness(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome,
+        _fitness_a: f32,
+        parent_b: &Chromosome,
+        _fitness_b: f32,
+    ) -> (Chromosome, Chromosome) {
+        self.crossover_pair(rng, parent_a, parent_b)
+    }
+    //кроссовер вместе с именем примененного метода для родословной (Genealogy).
+    //Комбинаторы (OneOf, AdaptiveOperators) возвращают имя оператора, выбранного для ребенка
+    fn crossover_named(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome,
+        fitness_a: f32,
+        parent_b: &Chromosome,
+        fitness_b: f32,
+    ) -> (Chromosome, &'static str) {
+        (self.crossover_with_fitness(rng, parent_a, fitness_a, parent_b, fitness_b), self.name())
+    }
+    //два ребенка вместе с именем примененного метода
+    fn crossover_pair_named(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome,
+        fitness_a: f32,
+        parent_b: &Chromosome,
+        fitness_b: f32,
+    ) -> ((Chromosome, Chromosome), &'static str) {
+        (self.crossover_pair_with_fitness(rng, parent_a, fitness_a, parent_b, fitness_b), self.name())
+    }
+    //имя метода для родословной (Genealogy), по умолчанию - имя типа
+    fn name(&self) -> &'static str {
+        type_short_name::<Self>()
+    }
+    //пригодность детей, созданных с прошлого вызова, в порядке создания (у пары - 2 ребенка).
+    //Нужна адаптивным методам (AdaptiveOperators), вызывается в начале evolve.
+    //По умолчанию не используется
+    fn credit(&self, _fitnesses: &[f32]) {
+    }
+    //забыть детей, созданных с прошлого вызова credit, без пригодности. Алгоритм вызывает
+    //в начале evolve, если детей прошлого поколения нет в популяции, и при ошибке evolve
+    fn reset_credit(&self) {
+    }
+}
+
+//Родители в случайном порядке: структура 1-го остается базовой. true - порядок поменян
+pub(crate) fn random_base<'a>(
+    rng: &mut dyn RngCore,
+    parent_a: &'a Chromosome,
+    parent_b: &'a Chromosome,
+) -> (&'a Chromosome, &'a Chromosome, bool) {
+    if rng.gen_bool(0.5) {
+        (parent_a, parent_b, false)
+    } else {
+        (parent_b, parent_a, true)
+    }
+}
+
+//Выравнивание генов: для каждого гена base - индекс парного гена в other.
+//Родители одной структуры (равная длина и те же (layer, neuron_out, neuron_in) по порядку)
+//выравниваются по позиции, иначе (NEAT-геномы разной структуры) - по ключу
+//(layer, neuron_out, neuron_in). Повторяющиеся ключи сопоставляются по порядку вхождения.
+//Гены первого слоя (входы) не смешиваются и пары не имеют.
+pub(crate) fn align(base: &Chromosome, other: &Chromosome) -> Vec<Option<usize>> {
+    let key = |(_, l, o, i): (f32, usize, usize, usize)| (l, o, i);
+    let input = |(_, l, _, _): (f32, usize, usize, usize)| l == 1;
+    if base.len() == other.len() && base.iter().zip(other.iter()).all(|(a, b)| key(a) == key(b)) {
+        return base.iter()
+            .enumerate()
+            .map(|(j, gene)| (!input(gene)).then_some(j))
+            .collect();
+    }
+    let mut indices: HashMap<(usize, usize, usize), VecDeque<usize>> = HashMap::new();
+    for (j, gene) in other.iter().enumerate() {
+        indices.entry(key(gene)).or_default().push_back(j);
+    }
+    base.iter()
+        .map(|gene| {
+            let j = indices.get_mut(&key(gene)).and_then(|queue| queue.pop_front());
+            j.filter(|_| !input(gene))
+        })
+        .collect()
+}
+
+//Ребенок со структурой base: вес парного гена считается через
+//weight(индекс гена в base, вес base, вес other), остальные гены - от base
+pub(crate) fn aligned_child(
+    base: &Chromosome,
+    other: &Chromosome,
+    mut weight: impl FnMut(usize, f32, f32) -> f32,
+) -> Chromosome {
+    base.iter()
+        .zip(align(base, other))
+        .enumerate()
+        .map(|(j, ((w1, l, o, i), pair))| match pair {
+            Some(k) => (weight(j, w1, other[k].0), l, o, i),
+            None => (w1, l, o, i),
+        })
+        .collect()
+}
+
+//Пара детей: 1-й со структурой base, 2-й - со структурой other. Для парных генов
+//weight(индекс гена в base, вес base, вес other) возвращает веса (1-го ребенка, 2-го),
+//остальные гены каждый ребенок берет от своего родителя
+pub(crate) fn aligned_children(
+    base: &Chromosome,
+    other: &Chromosome,
+    mut weight: impl FnMut(usize, f32, f32) -> (f32, f32),
+) -> (Chromosome, Chromosome) {
+    let mut sibling: Vec<(f32, usize, usize, usize)> = other.iter().collect();
+    let child = base.iter()
+        .zip(align(base, other))
+        .enumerate()
+        .map(|(j, ((w1, l, o, i), pair))| match pair {
+            Some(k) => {
+                let (c1, c2) = weight(j, w1, other[k].0);
+                sibling[k].0 = c2;
+                (c1, l, o, i)
+            }
+            None => (w1, l, o, i),
+        })
+        .collect();
+    (child, sibling.into_iter().collect())
+}
+
+//Ребенок с выравниванием генов (align) и структурой случайного родителя: для парных генов
+//вес ребенка считается через weight(rng, вес базового, вес 2-го)
+pub(crate) fn crossover_aligned(
+    rng: &mut dyn RngCore,
+    parent_a: &Chromosome,
+    parent_b: &Chromosome,
+    mut weight: impl FnMut(&mut dyn RngCore, f32, f32) -> f32,
+) -> Chromosome {
+    let (base, other, _) = random_base(rng, parent_a, parent_b);
+    aligned_child(base, other, |_, w1, w2| weight(rng, w1, w2))
+}
+
+//Пара детей как в aligned_children, базовый родитель - случайный
+pub(crate) fn crossover_aligned_pair(
+    rng: &mut dyn RngCore,
+    parent_a: &Chromosome,
+    parent_b: &Chromosome,
+    mut weight: impl FnMut(&mut dyn RngCore, f32, f32) -> (f32, f32),
+) -> (Chromosome, Chromosome) {
+    let (base, other, _) = random_base(rng, parent_a, parent_b);
+    aligned_children(base, other, |_, w1, w2| weight(rng, w1, w2))
+}
+
+//Кроссовер функциональных блоков: структура случайного родителя остается базовой, а для
+//каждого блока генов (unit - номер блока по гену) решается один раз, от какого родителя
+//берутся все его парные веса. Входы первого слоя не меняются.
+pub(crate) fn crossover_units(
+    rng: &mut dyn RngCore,
+    parent_a: &Chromosome,
+    parent_b: &Chromosome,
+    unit: impl Fn((f32, usize, usize, usize)) -> usize,
+) -> Chromosome {
+    let (base, other, _) = random_base(rng, parent_a, parent_b);
+    let mut from_b: HashMap<usize, bool> = HashMap::new();//блок берется от 2-го родителя
+    aligned_child(base, other, |j, w1, w2| {
+        let take_b = *from_b.entry(unit(base[j])).or_insert_with(|| rng.gen_bool(0.5));
+        if take_b { w2 } else { w1 }
+    })
+}
+
+//Пара детей crossover_units: решение по блоку принимается один раз, и блок, взятый
+//1-м ребенком от одного родителя, 2-й берет от другого
+pub(crate) fn crossover_units_pair(
+    rng: &mut dyn RngCore,
+    parent_a: &Chromosome,
+    parent_b: &Chromosome,
+    unit: impl Fn((f32, usize, usize, usize)) -> usize,
+) -> (Chromosome, Chromosome) {
+    let (base, other, _) = random_base(rng, parent_a, parent_b);
+    let mut from_b: HashMap<usize, bool> = HashMap::new();
+    aligned_children(base, other, |j, w1, w2| {
+        let take_b = *from_b.entry(unit(base[j])).or_insert_with(|| rng.gen_bool(0.5));
+        if take_b { (w2, w1) } else { (w1, w2) }
+    })
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    //Сеть родителя для тестов кроссоверов: слои 2..=layers + 1 по 10 нейронов, у каждого
+    //смещение и 2 входа от нейронов предыдущего слоя, все веса равны w
+    pub(crate) fn network(w: f32, layers: usize) -> Chromosome {
+        (2..=layers + 1)
+            .flat_map(|l| (0..10).flat_map(move |k| {
+                let (n, prev) = (10 * l + k, 10 * (l - 1));
+                [(w, l, n, 0), (w, l, n, prev + k), (w, l, n, prev + (k + 1) % 10)]
+            }))
+            .collect()
+    }
+    //Родители разной структуры (как у NEAT): входы первого слоя с номерами сенсоров, нейроны,
+    //которых нет у другого родителя, и пропущенные или переставленные связи
+    pub(crate) fn unequal_parents(w_a: f32, w_b: f32) -> (Chromosome, Chromosome) {
+        let parent_a = vec![
+            (w_a,1,1,0), (w_a,1,1,1), (w_a,1,2,0), (w_a,1,2,2),//1,2
+            (w_a,2,3,0), (w_a,2,3,1), (w_a,2,3,2),//3
+            (w_a,2,4,0), (w_a,2,4,1),//4 - только у 1-го
+            (w_a,3,6,0), (w_a,3,6,3), (w_a,3,6,4),//6
+        ];
+        let parent_b = vec![
+            (w_b,1,1,0), (w_b,1,1,1), (w_b,1,2,0), (w_b,1,2,2),//1,2
+            (w_b,2,3,0), (w_b,2,3,2),//3 - без связи от 1
+            (w_b,2,5,0), (w_b,2,5,1), (w_b,2,5,2),//5 - только у 2-го
+            (w_b,3,6,0), (w_b,3,6,5), (w_b,3,6,3),//6
+        ];
+        (parent_a.into_iter().collect(), parent_b.into_iter().collect())
+    }
+    //Ребенок родителей разной структуры: структура одного из родителей (базового), входы
+    //первого слоя и гены без пары - от него. Возвращает для парных генов
+    //(индекс гена в ребенке, вес базового, вес 2-го родителя, вес ребенка)
+    pub(crate) fn paired_weights(
+        child: &Chromosome,
+        parent_a: &Chromosome,
+        parent_b: &Chromosome,
+    ) -> Vec<(usize, f32, f32, f32)> {
+        let keys = |c: &Chromosome| -> Vec<(usize, usize, usize)> {
+            c.iter().map(|(_, l, o, i)| (l, o, i)).collect()
+        };
+        let (base, other) = if keys(child) == keys(parent_a) { (parent_a, parent_b) } else { (parent_b, parent_a) };
+        assert_eq!(keys(child), keys(base));
+        let mut paired = Vec::new();
+        for (j, ((w, l, o, i), (w1, _, _, _))) in child.iter().zip(base.iter()).enumerate() {
+            match other.iter().find(|(_, l2, o2, i2)| (*l2, *o2, *i2) == (l, o, i) && l > 1) {
+                Some((w2, _, _, _)) => paired.push((j, w1, w2, w)),
+                None => assert_eq!(w, w1),
+            }
+        }
+        paired
+    }
+
+    #[test]
+    fn alignment() {
+        //одна структура - по позиции, хотя у фиктивных генов слоя 0 один ключ
+        let parent_a: Chromosome = vec![(1.0,0,0,0), (2.0,0,0,0), (3.0,0,0,0)].into_iter().collect();
+        let parent_b: Chromosome = vec![(4.0,0,0,0), (5.0,0,0,0), (6.0,0,0,0)].into_iter().collect();
+        assert_eq!(align(&parent_a, &parent_b), vec![Some(0), Some(1), Some(2)]);
+
+        //разная структура - по ключу, входы первого слоя без пары
+        let parent_a: Chromosome = vec![(1.0,1,1,1), (0.1,2,2,0), (0.2,2,2,1), (0.3,2,3,0)]
+            .into_iter()
+            .collect();
+        let parent_b: Chromosome = vec![(1.0,1,1,1), (0.3,2,3,0), (0.1,2,2,0)].into_iter().collect();
+        assert_eq!(align(&parent_a, &parent_b), vec![None, Some(2), None, Some(1)]);
+
+        let (child_a, child_b) = aligned_children(&parent_a, &parent_b, |_, w1, w2| (w2, w1));
+        assert_eq!(child_a, parent_a);
+        assert_eq!(child_b, parent_b);
+    }
 }
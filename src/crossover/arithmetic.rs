@@ -0,0 +1,101 @@
+use crate::*;
+//Арифметический кроссовер весов: w = a*w1 + (1-a)*w2.
+//Гены выравниваются функцией align, а вес ребенка, в отличие от UniformCrossover,
+//может лежать между весами родителей.
+#[derive(Clone, Debug)]
+pub struct ArithmeticCrossover {
+    alpha: f32,//доля веса базового родителя
+}
+
+impl ArithmeticCrossover {
+    pub fn new(alpha: f32) -> Self {
+        Self::try_new(alpha).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    pub fn try_new(alpha: f32) -> Result<Self, Error> {
+        if !(0.0..=1.0).contains(&alpha) {
+            return Err(Error::InvalidParameter { name: "alpha", value: alpha });
+        }
+        Ok(Self { alpha })
+    }
+}
+
+impl CrossoverMethod for ArithmeticCrossover {
+    fn crossover(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome,
+        parent_b: &Chromosome,
+    ) -> Chromosome {
+        crossover_aligned(rng, parent_a, parent_b, |_, w1, w2| {
+            self.alpha * w1 + (1.0 - self.alpha) * w2
+        })
+    }
+    //дополняющие дети: a*w1 + (1-a)*w2 и (1-a)*w1 + a*w2
+    fn crossover_pair(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome,
+        parent_b: &Chromosome,
+    ) -> (Chromosome, Chromosome) {
+        crossover_aligned_pair(rng, parent_a, parent_b, |_, w1, w2| {
+            (self.alpha * w1 + (1.0 - self.alpha) * w2, (1.0 - self.alpha) * w1 + self.alpha * w2)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crossover::tests::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let parent_a: Chromosome = vec![(1.0,1,1,1), (0.0,2,2,0), (4.0,2,2,1)].into_iter().collect();
+        let parent_b: Chromosome = vec![(0.0,1,1,1), (2.0,2,2,0), (8.0,2,2,1), (1.0,2,3,0)]
+            .into_iter()
+            .collect();
+
+        let child = ArithmeticCrossover::new(0.5).crossover(&mut rng, &parent_a, &parent_b);
+        let weights: Vec<f32> = child.iter().skip(1).map(|(w, _, _, _)| w).take(2).collect();
+
+        approx::assert_relative_eq!(weights.as_slice(), [1.0, 6.0].as_slice());
+    }
+
+    #[test]
+    fn pair() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let parent_a: Chromosome = vec![(0.0,2,2,0), (4.0,2,2,1)].into_iter().collect();
+        let parent_b: Chromosome = vec![(2.0,2,2,0), (8.0,2,2,1), (1.0,2,3,0)].into_iter().collect();
+
+        let (child_a, child_b) =
+            ArithmeticCrossover::new(0.25).crossover_pair(&mut rng, &parent_a, &parent_b);
+        //дети со структурами обоих родителей, сумма весов родителей сохраняется
+        assert_eq!(child_a.len() + child_b.len(), 5);
+        let sum = |c: &Chromosome| c.iter().map(|(w, _, o, _)| if o == 2 { w } else { 0.0 }).sum::<f32>();
+        approx::assert_relative_eq!(sum(&child_a) + sum(&child_b), 0.0 + 4.0 + 2.0 + 8.0);
+        assert!(child_a.iter().chain(child_b.iter()).any(|(w, ..)| w == 0.5 || w == 1.5));
+    }
+
+    #[test]
+    fn different_structure() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let (parent_a, parent_b) = unequal_parents(1.0, 3.0);
+        let crossover = ArithmeticCrossover::new(0.25);
+
+        for _ in 0..10 {
+            let child = crossover.crossover(&mut rng, &parent_a, &parent_b);
+            let paired = paired_weights(&child, &parent_a, &parent_b);
+            assert_eq!(paired.len(), 4);
+            assert!(paired.iter().all(|(_, w1, w2, w)| (w - (0.25 * w1 + 0.75 * w2)).abs() < 1e-6));
+
+            let (child_a, child_b) = crossover.crossover_pair(&mut rng, &parent_a, &parent_b);
+            let paired = [paired_weights(&child_a, &parent_a, &parent_b),
+                          paired_weights(&child_b, &parent_a, &parent_b)].concat();
+            approx::assert_relative_eq!(paired.iter().map(|(_, _, _, w)| w).sum::<f32>(), 4.0 * (1.0 + 3.0));
+        }
+    }
+}
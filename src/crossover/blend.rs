@@ -0,0 +1,103 @@
+use crate::*;
+//Кроссовер смешиванием BLX-alpha: вес ребенка случайный из отрезка
+//[min - alpha*d, max + alpha*d], где d = |w1 - w2|.
+//Гены выравниваются функцией align.
+#[derive(Clone, Debug)]
+pub struct BlendCrossover {
+    alpha: f32,//расширение отрезка, обычно 0.5
+}
+
+impl BlendCrossover {
+    pub fn new(alpha: f32) -> Self {
+        Self::try_new(alpha).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    pub fn try_new(alpha: f32) -> Result<Self, Error> {
+        if !(alpha.is_finite() && alpha >= 0.0) {
+            return Err(Error::InvalidParameter { name: "alpha", value: alpha });
+        }
+        Ok(Self { alpha })
+    }
+}
+
+impl CrossoverMethod for BlendCrossover {
+    fn crossover(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome,
+        parent_b: &Chromosome,
+    ) -> Chromosome {
+        crossover_aligned(rng, parent_a, parent_b, |rng, w1, w2| {
+            let d = (w1 - w2).abs();
+            let lo = w1.min(w2) - self.alpha * d;
+            let hi = w1.max(w2) + self.alpha * d;
+            if hi > lo { rng.gen_range(lo..hi) } else { w1 }
+        })
+    }
+    //дети из одного случайного числа, симметричные относительно среднего весов родителей
+    fn crossover_pair(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome,
+        parent_b: &Chromosome,
+    ) -> (Chromosome, Chromosome) {
+        crossover_aligned_pair(rng, parent_a, parent_b, |rng, w1, w2| {
+            let d = (w1 - w2).abs();
+            let lo = w1.min(w2) - self.alpha * d;
+            let hi = w1.max(w2) + self.alpha * d;
+            if hi <= lo { return (w1, w2); }
+            let u = rng.gen::<f32>();
+            (lo + u * (hi - lo), hi - u * (hi - lo))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crossover::tests::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let parent_a = network(1.0, 4);
+        let parent_b = network(3.0, 4);
+
+        let child = BlendCrossover::new(0.5).crossover(&mut rng, &parent_a, &parent_b);
+
+        //все веса в [0, 4], часть - за пределами отрезка между родителями
+        assert!(child.iter().all(|(w, _, _, _)| (0.0..4.0).contains(&w)));
+        assert!(child.iter().any(|(w, _, _, _)| w < 1.0));
+        assert!(child.iter().any(|(w, _, _, _)| w > 3.0));
+        assert!(child.iter().any(|(w, _, _, _)| w > 1.0 && w < 3.0));
+
+        let (child_a, child_b) = BlendCrossover::new(0.5).crossover_pair(&mut rng, &parent_a, &parent_b);
+        assert!(child_a.iter().zip(child_b.iter()).all(|((a, ..), (b, ..))| (a + b - 4.0).abs() < 1e-5));
+        assert!(child_a.iter().any(|(w, _, _, _)| w < 1.0));
+
+        //бесконечный alpha дал бы бесконечный отрезок
+        assert!(BlendCrossover::try_new(f32::INFINITY).is_err());
+        assert!(BlendCrossover::try_new(-0.5).is_err());
+    }
+
+    #[test]
+    fn different_structure() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let (parent_a, parent_b) = unequal_parents(1.0, 3.0);
+        let crossover = BlendCrossover::new(0.5);
+
+        for _ in 0..10 {
+            let child = crossover.crossover(&mut rng, &parent_a, &parent_b);
+            let paired = paired_weights(&child, &parent_a, &parent_b);
+            assert_eq!(paired.len(), 4);
+            assert!(paired.iter().all(|(_, _, _, w)| (0.0..4.0).contains(w)));
+
+            let (child_a, child_b) = crossover.crossover_pair(&mut rng, &parent_a, &parent_b);
+            let paired = [paired_weights(&child_a, &parent_a, &parent_b),
+                          paired_weights(&child_b, &parent_a, &parent_b)].concat();
+            approx::assert_relative_eq!(paired.iter().map(|(_, _, _, w)| w).sum::<f32>(), 4.0 * (1.0 + 3.0));
+        }
+    }
+}
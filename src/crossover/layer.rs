@@ -0,0 +1,77 @@
+use crate::*;
+//Послойный кроссовер: все нейроны слоя (смещения и входные веса) наследуются
+//от одного из родителей. Структура сети - случайного родителя.
+#[derive(Clone, Debug, Default)]
+pub struct LayerCrossover;
+
+impl CrossoverMethod for LayerCrossover {
+    fn crossover(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome,
+        parent_b: &Chromosome,
+    ) -> Chromosome {
+        crossover_units(rng, parent_a, parent_b, |(_, layer_num, _, _)| layer_num)
+    }
+    //дополняющие дети: слой, взятый 1-м ребенком от одного родителя, 2-й берет от другого
+    fn crossover_pair(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome,
+        parent_b: &Chromosome,
+    ) -> (Chromosome, Chromosome) {
+        crossover_units_pair(rng, parent_a, parent_b, |(_, layer_num, _, _)| layer_num)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crossover::tests::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let parent_a = network(1.0, 30);
+        let parent_b = network(2.0, 30);
+
+        let child = LayerCrossover.crossover(&mut rng, &parent_a, &parent_b);
+
+        //все гены слоя от одного родителя
+        let mut layers: HashMap<usize, HashSet<u32>> = HashMap::new();
+        for (w, layer_num, _, _) in child.iter() {
+            layers.entry(layer_num).or_default().insert(w as u32);
+        }
+        assert!(layers.values().all(|parents| parents.len() == 1));
+        assert!(child.iter().any(|(w, _, _, _)| w == 1.0));
+        assert!(child.iter().any(|(w, _, _, _)| w == 2.0));
+
+        let (child_a, child_b) = LayerCrossover.crossover_pair(&mut rng, &parent_a, &parent_b);
+        assert!(child_a.iter().zip(child_b.iter()).all(|((a, ..), (b, ..))| a + b == 3.0));
+        assert!(child_a.iter().any(|(w, _, _, _)| w == 1.0));
+        assert!(child_a.iter().any(|(w, _, _, _)| w == 2.0));
+    }
+
+    #[test]
+    fn different_structure() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let (parent_a, parent_b) = unequal_parents(1.0, 2.0);
+
+        for _ in 0..10 {
+            let child = LayerCrossover.crossover(&mut rng, &parent_a, &parent_b);
+            //парные веса слоя от одного родителя
+            let mut layers: HashMap<usize, HashSet<u32>> = HashMap::new();
+            for (j, _, _, w) in paired_weights(&child, &parent_a, &parent_b) {
+                layers.entry(child[j].1).or_default().insert(w as u32);
+            }
+            assert!(layers.values().all(|parents| parents.len() == 1));
+
+            let (child_a, child_b) = LayerCrossover.crossover_pair(&mut rng, &parent_a, &parent_b);
+            let paired = [paired_weights(&child_a, &parent_a, &parent_b),
+                          paired_weights(&child_b, &parent_a, &parent_b)].concat();
+            approx::assert_relative_eq!(paired.iter().map(|(_, _, _, w)| w).sum::<f32>(), 4.0 * (1.0 + 2.0));
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use crate::*;
+//N-точечный кроссовер: упорядоченный список генов (как после update_genes:
+//слой -> нейрон -> вход) режется в points случайных точках, отрезки берутся поочередно
+//от родителей. Разную длину родителей учитывает выравнивание (align): структура
+//случайного родителя остается базовой, а ген, которого нет во 2-м родителе, и входы
+//первого слоя берутся от базового.
+#[derive(Clone, Debug)]
+pub struct MultiPointCrossover {
+    points: usize,//кол. точек разреза
+}
+
+impl MultiPointCrossover {
+    pub fn new(points: usize) -> Self {
+        Self::try_new(points).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    pub fn try_new(points: usize) -> Result<Self, Error> {
+        if points == 0 {
+            return Err(Error::InvalidParameter { name: "points", value: points as f32 });
+        }
+        Ok(Self { points })
+    }
+}
+
+impl MultiPointCrossover {
+    //точки разреза - между генами родителя длины len, по возрастанию
+    fn cuts(&self, rng: &mut dyn RngCore, len: usize) -> Vec<usize> {
+        if len < 2 {
+            return Vec::new();
+        }
+        let mut cuts: Vec<usize> = rand::seq::index::sample(rng, len - 1, self.points.min(len - 1))
+            .into_iter()
+            .map(|cut| cut + 1)
+            .collect();
+        cuts.sort();
+        cuts
+    }
+}
+
+//ген j базового родителя - в отрезке от 2-го родителя (нечетное кол. разрезов до него)
+fn from_b(cuts: &[usize], j: usize) -> bool {
+    cuts.partition_point(|cut| *cut <= j) % 2 == 1
+}
+
+impl CrossoverMethod for MultiPointCrossover {
+    fn crossover(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome,
+        parent_b: &Chromosome,
+    ) -> Chromosome {
+        let (base, other, _) = random_base(rng, parent_a, parent_b);
+        let cuts = self.cuts(rng, base.len());
+        aligned_child(base, other, |j, w1, w2| if from_b(&cuts, j) { w2 } else { w1 })
+    }
+    //дополняющие дети: отрезки, взятые 1-м ребенком от одного родителя, 2-й берет от другого
+    fn crossover_pair(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome,
+        parent_b: &Chromosome,
+    ) -> (Chromosome, Chromosome) {
+        let (base, other, _) = random_base(rng, parent_a, parent_b);
+        let cuts = self.cuts(rng, base.len());
+        aligned_children(base, other, |j, w1, w2| if from_b(&cuts, j) { (w2, w1) } else { (w1, w2) })
+    }
+}
+
+//Одноточечный кроссовер, MultiPointCrossover с одной точкой разреза
+#[derive(Clone, Debug, Default)]
+pub struct SinglePointCrossover;
+
+impl CrossoverMethod for SinglePointCrossover {
+    fn crossover(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome,
+        parent_b: &Chromosome,
+    ) -> Chromosome {
+        MultiPointCrossover::new(1).crossover(rng, parent_a, parent_b)
+    }
+
+    fn crossover_pair(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome,
+        parent_b: &Chromosome,
+    ) -> (Chromosome, Chromosome) {
+        MultiPointCrossover::new(1).crossover_pair(rng, parent_a, parent_b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crossover::tests::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    //кол. отрезков подряд идущих генов от одного родителя
+    fn segments(child: &Chromosome) -> usize {
+        let weights: Vec<f32> = child.iter().map(|(w, _, _, _)| w).collect();
+        1 + weights.windows(2).filter(|w| w[0] != w[1]).count()
+    }
+
+    #[test]
+    fn single_point() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let parent_a = network(1.0, 4);
+        let parent_b = network(2.0, 4);
+
+        let child = SinglePointCrossover.crossover(&mut rng, &parent_a, &parent_b);
+
+        assert_eq!(child.len(), 120);
+        assert_eq!(segments(&child), 2);
+    }
+
+    #[test]
+    fn multi_point() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let parent_a = network(1.0, 4);
+        let parent_b = network(2.0, 4);
+
+        let child = MultiPointCrossover::new(4).crossover(&mut rng, &parent_a, &parent_b);
+
+        assert_eq!(segments(&child), 5);
+
+        let (child_a, child_b) =
+            MultiPointCrossover::new(4).crossover_pair(&mut rng, &parent_a, &parent_b);
+        assert_eq!(segments(&child_a), 5);
+        assert!(child_a.iter().zip(child_b.iter()).all(|((a, ..), (b, ..))| a + b == 3.0));
+    }
+
+    #[test]
+    fn different_structure() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let (parent_a, parent_b) = unequal_parents(1.0, 2.0);
+
+        for _ in 0..10 {
+            let child = MultiPointCrossover::new(3).crossover(&mut rng, &parent_a, &parent_b);
+            //парные веса копируются от одного из родителей
+            let paired = paired_weights(&child, &parent_a, &parent_b);
+            assert_eq!(paired.len(), 4);
+            assert!(paired.iter().all(|(_, w1, w2, w)| w == w1 || w == w2));
+
+            let (child_a, child_b) = MultiPointCrossover::new(3).crossover_pair(&mut rng, &parent_a, &parent_b);
+            let paired = [paired_weights(&child_a, &parent_a, &parent_b),
+                          paired_weights(&child_b, &parent_a, &parent_b)].concat();
+            approx::assert_relative_eq!(paired.iter().map(|(_, _, _, w)| w).sum::<f32>(), 4.0 * (1.0 + 2.0));
+        }
+    }
+}
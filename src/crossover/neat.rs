@@ -0,0 +1,165 @@
+use crate::*;
+//Кроссовер по типу NEAT: гены родителей выравниваются по (layer, neuron_out, neuron_in).
+//Совпадающие гены наследуются случайно от любого родителя, а несовпадающие
+//(disjoint и excess) - от более пригодного родителя. При равной пригодности
+//несовпадающие гены берутся от обоих, т.е. ребенок получает объединение структур.
+//Нейрон, который у родителей в разных слоях, остается в слое 1-го родителя: его гены
+//и связи от него из 2-го родителя пропускаются.
+//Без пригодности (crossover) родители считаются равными.
+#[derive(Clone, Debug, Default)]
+pub struct NeatCrossover;
+
+impl CrossoverMethod for NeatCrossover {
+    fn crossover(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome,
+        parent_b: &Chromosome,
+    ) -> Chromosome {
+        self.crossover_with_fitness(rng, parent_a, 0.0, parent_b, 0.0)
+    }
+
+    fn crossover_with_fitness(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome,
+        fitness_a: f32,
+        parent_b: &Chromosome,
+        fitness_b: f32,
+    ) -> Chromosome {
+        //более пригодный родитель - первый
+        let (parents, union) = match fitness_a.partial_cmp(&fitness_b) {
+            Some(Ordering::Less) => ((parent_b, parent_a), false),
+            Some(Ordering::Greater) => ((parent_a, parent_b), false),
+            _ => ((parent_a, parent_b), true),
+        };
+        //парные гены 2-го родителя (входы первого слоя - от более пригодного)
+        let pairs = align(parents.0, parents.1);
+        let mut childs: Vec<(f32, usize, usize, usize)> = Vec::new();
+        let mut layers: HashMap<usize, usize> = HashMap::new();//нейрон -> слой у ребенка
+        for ((w1, l1, o1, i1), pair) in parents.0.iter().zip(&pairs) {
+            let value = match pair {
+                //совпадающий ген - от любого родителя
+                Some(k) if rng.gen_bool(0.5) => (parents.1[*k].0, l1, o1, i1),
+                //или несовпадающий ген более пригодного родителя
+                _ => (w1, l1, o1, i1),
+            };
+            layers.insert(o1, l1);
+            childs.push(value);
+        }
+        if union {
+            let child_map: HashSet<(usize, usize, usize)> = parents.0
+                .iter()
+                .map(|(_, l, o, i)| (l, o, i))
+                .collect();
+            //нейроны, которые у 2-го родителя в другом слое
+            let skipped: HashSet<usize> = parents.1
+                .iter()
+                .filter(|(_, l, o, _)| layers.get(o).is_some_and(|layer| *layer != *l))
+                .map(|(_, _, o, _)| o)
+                .collect();
+            for (w2, l2, o2, i2) in parents.1.iter() {
+                if child_map.contains(&(l2, o2, i2)) { continue; }//уже есть
+                //нейрон не может оказаться в разных слоях
+                if skipped.contains(&o2) { continue; }
+                //связь от пропущенного нейрона (на слое 1 входы - не нейроны)
+                if l2 > 1 && i2 != 0 && skipped.contains(&i2) { continue; }
+                childs.push((w2, l2, o2, i2));
+            }
+            //гены по порядку: слой -> нейрон -> вход
+            if !childs.iter().any(|&(_, l, _, _)| l == 0) {
+                let (inp_links, neurons) = Chromosome::from_weights_to_flex_net_view(childs);
+                let mut child = Chromosome::new(Vec::new());
+                child.update_genes(inp_links, neurons);
+                return child;
+            }
+        }
+        Chromosome::new(childs)
+    }
+    //два независимых ребенка, с учетом пригодности родителей
+    fn crossover_pair_with_fitness(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome,
+        fitness_a: f32,
+        parent_b: &Chromosome,
+        fitness_b: f32,
+    ) -> (Chromosome, Chromosome) {
+        (self.crossover_with_fitness(rng, parent_a, fitness_a, parent_b, fitness_b),
+         self.crossover_with_fitness(rng, parent_a, fitness_a, parent_b, fitness_b))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn parents() -> (Chromosome, Chromosome) {
+        let parent_a = Chromosome::new(vec![
+            (0.0,1,1,0), (1.0,1,1,1),//1
+            (0.1,2,2,0), (0.2,2,2,1),//2
+            (0.3,2,3,0), (0.4,2,3,1),//3 - только у a
+            (0.5,3,5,0), (0.6,3,5,2), (0.7,3,5,3),//5
+        ]);
+        let parent_b = Chromosome::new(vec![
+            (0.0,1,1,0), (1.0,1,1,1),//1
+            (-0.1,2,2,0), (-0.2,2,2,1),//2
+            (-0.3,2,4,0), (-0.4,2,4,1),//4 - только у b
+            (-0.5,3,5,0), (-0.6,3,5,2), (-0.8,3,5,4),//5
+        ]);
+        (parent_a, parent_b)
+    }
+
+    fn keys(chromosome: &Chromosome) -> HashSet<(usize, usize, usize)> {
+        chromosome.iter().map(|(_, l, o, i)| (l, o, i)).collect()
+    }
+
+    #[test]
+    fn fitter_parent_keeps_its_structure() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let (parent_a, parent_b) = parents();
+
+        let child = NeatCrossover.crossover_with_fitness(&mut rng, &parent_a, 1.0, &parent_b, 2.0);
+
+        assert_eq!(keys(&child), keys(&parent_b));
+        //совпадающие гены от обоих родителей, несовпадающие - от b
+        let weights: Vec<f32> = child.iter().map(|(w, _, _, _)| w).collect();
+        assert!(weights.iter().any(|w| *w > 0.0));
+        assert!(child.iter().all(|(w, _, o, _)| o != 4 || w < 0.0));
+    }
+
+    #[test]
+    fn equal_fitness_gives_union_of_structures() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let (parent_a, parent_b) = parents();
+
+        let child = NeatCrossover.crossover(&mut rng, &parent_a, &parent_b);
+
+        let union: HashSet<_> = keys(&parent_a).union(&keys(&parent_b)).copied().collect();
+        assert_eq!(keys(&child), union);
+        assert_eq!(child.len(), 12);
+        //гены упорядочены по слоям и нейронам
+        let order: Vec<(usize, usize)> = child.iter().map(|(_, l, o, _)| (l, o)).collect();
+        assert!(order.windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    #[test]
+    fn union_skips_links_from_neuron_in_other_layer() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let (parent_a, parent_b) = parents();
+        //у b нейрон 3 в слое 3 (у a - в слое 2), а нейрон 6 слоя 4 связан с ним
+        let mut genes: Vec<(f32, usize, usize, usize)> = parent_b.iter().collect();
+        genes.extend([(0.9,3,3,0), (0.9,3,3,2), (0.9,4,6,0), (0.9,4,6,3), (0.9,4,6,5)]);
+        let parent_b = Chromosome::new(genes);
+
+        let child = NeatCrossover.crossover(&mut rng, &parent_a, &parent_b);
+
+        let keys = keys(&child);
+        assert!(!keys.contains(&(3, 3, 0)) && !keys.contains(&(3, 3, 2)));
+        assert!(!keys.contains(&(4, 6, 3)));
+        assert!(keys.contains(&(4, 6, 0)) && keys.contains(&(4, 6, 5)));
+        assert!(keys.contains(&(2, 3, 0)) && keys.contains(&(2, 3, 1)));
+    }
+}
@@ -0,0 +1,79 @@
+use crate::*;
+//Понейронный кроссовер: нейрон (смещение и все входные веса) наследуется целиком
+//от одного из родителей, поэтому совместно подобранные веса нейрона не разрываются,
+//как в UniformCrossover. Структура сети - случайного родителя.
+#[derive(Clone, Debug, Default)]
+pub struct NeuronCrossover;
+
+impl CrossoverMethod for NeuronCrossover {
+    fn crossover(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome,
+        parent_b: &Chromosome,
+    ) -> Chromosome {
+        crossover_units(rng, parent_a, parent_b, |(_, _, neuron_out, _)| neuron_out)
+    }
+    //дополняющие дети: нейрон, взятый 1-м ребенком от одного родителя, 2-й берет от другого
+    fn crossover_pair(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome,
+        parent_b: &Chromosome,
+    ) -> (Chromosome, Chromosome) {
+        crossover_units_pair(rng, parent_a, parent_b, |(_, _, neuron_out, _)| neuron_out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crossover::tests::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let parent_a = network(1.0, 4);
+        let parent_b = network(2.0, 4);
+
+        let child = NeuronCrossover.crossover(&mut rng, &parent_a, &parent_b);
+
+        //все гены нейрона от одного родителя
+        let mut neurons: HashMap<usize, HashSet<u32>> = HashMap::new();
+        for (w, _, n_out, _) in child.iter() {
+            neurons.entry(n_out).or_default().insert(w as u32);
+        }
+        assert!(neurons.values().all(|parents| parents.len() == 1));
+        //и нейроны от обоих родителей
+        assert!(child.iter().any(|(w, _, _, _)| w == 1.0));
+        assert!(child.iter().any(|(w, _, _, _)| w == 2.0));
+
+        let (child_a, child_b) = NeuronCrossover.crossover_pair(&mut rng, &parent_a, &parent_b);
+        assert!(child_a.iter().zip(child_b.iter()).all(|((a, ..), (b, ..))| a + b == 3.0));
+        assert!(child_a.iter().any(|(w, _, _, _)| w == 1.0));
+        assert!(child_a.iter().any(|(w, _, _, _)| w == 2.0));
+    }
+
+    #[test]
+    fn different_structure() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let (parent_a, parent_b) = unequal_parents(1.0, 2.0);
+
+        for _ in 0..10 {
+            let child = NeuronCrossover.crossover(&mut rng, &parent_a, &parent_b);
+            //парные веса нейрона от одного родителя
+            let mut neurons: HashMap<usize, HashSet<u32>> = HashMap::new();
+            for (j, _, _, w) in paired_weights(&child, &parent_a, &parent_b) {
+                neurons.entry(child[j].2).or_default().insert(w as u32);
+            }
+            assert!(neurons.values().all(|parents| parents.len() == 1));
+
+            let (child_a, child_b) = NeuronCrossover.crossover_pair(&mut rng, &parent_a, &parent_b);
+            let paired = [paired_weights(&child_a, &parent_a, &parent_b),
+                          paired_weights(&child_b, &parent_a, &parent_b)].concat();
+            approx::assert_relative_eq!(paired.iter().map(|(_, _, _, w)| w).sum::<f32>(), 4.0 * (1.0 + 2.0));
+        }
+    }
+}
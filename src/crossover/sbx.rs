@@ -0,0 +1,104 @@
+use crate::*;
+//Имитация двоичного кроссовера (SBX): вес ребенка 0.5*((1+b)*w1 + (1-b)*w2),
+//где b случайный с распределением, зависящим от индекса eta. Чем больше eta,
+//тем ближе дети к родителям. Гены выравниваются функцией align.
+#[derive(Clone, Debug)]
+pub struct SimulatedBinaryCrossover {
+    eta: f32,//индекс распределения, обычно 2..20
+}
+
+impl SimulatedBinaryCrossover {
+    pub fn new(eta: f32) -> Self {
+        Self::try_new(eta).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    pub fn try_new(eta: f32) -> Result<Self, Error> {
+        if !(0.0..).contains(&eta) {
+            return Err(Error::InvalidParameter { name: "eta", value: eta });
+        }
+        Ok(Self { eta })
+    }
+}
+
+impl CrossoverMethod for SimulatedBinaryCrossover {
+    fn crossover(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome,
+        parent_b: &Chromosome,
+    ) -> Chromosome {
+        crossover_aligned(rng, parent_a, parent_b, |rng, w1, w2| {
+            let beta = sbx_beta(rng.gen::<f32>(), self.eta);
+            0.5 * ((1.0 + beta) * w1 + (1.0 - beta) * w2)
+        })
+    }
+    //дополняющие дети с одним коэффициентом разброса, симметричные относительно
+    //среднего весов родителей
+    fn crossover_pair(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome,
+        parent_b: &Chromosome,
+    ) -> (Chromosome, Chromosome) {
+        crossover_aligned_pair(rng, parent_a, parent_b, |rng, w1, w2| {
+            let beta = sbx_beta(rng.gen::<f32>(), self.eta);
+            (0.5 * ((1.0 + beta) * w1 + (1.0 - beta) * w2),
+             0.5 * ((1.0 - beta) * w1 + (1.0 + beta) * w2))
+        })
+    }
+}
+
+//коэффициент разброса SBX для случайного u из [0, 1)
+pub(crate) fn sbx_beta(u: f32, eta: f32) -> f32 {
+    if u <= 0.5 {
+        (2.0 * u).powf(1.0 / (eta + 1.0))
+    } else {
+        (1.0 / (2.0 * (1.0 - u))).powf(1.0 / (eta + 1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crossover::tests::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let parent_a = network(1.0, 4);
+        let parent_b = network(3.0, 4);
+
+        let near = SimulatedBinaryCrossover::new(20.0).crossover(&mut rng, &parent_a, &parent_b);
+        let far = SimulatedBinaryCrossover::new(0.0).crossover(&mut rng, &parent_a, &parent_b);
+
+        //среднее отклонение от ближайшего родителя меньше при большом eta
+        let spread = |child: &Chromosome| -> f32 {
+            child.iter().map(|(w, _, _, _)| (w - 1.0).abs().min((w - 3.0).abs())).sum()
+        };
+        assert!(spread(&near) < spread(&far));
+
+        approx::assert_relative_eq!(sbx_beta(0.5, 2.0), 1.0);
+        approx::assert_relative_eq!(sbx_beta(0.0, 2.0), 0.0);
+    }
+
+    #[test]
+    fn different_structure() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let (parent_a, parent_b) = unequal_parents(1.0, 3.0);
+        let crossover = SimulatedBinaryCrossover::new(2.0);
+
+        for _ in 0..10 {
+            let child = crossover.crossover(&mut rng, &parent_a, &parent_b);
+            let paired = paired_weights(&child, &parent_a, &parent_b);
+            assert_eq!(paired.len(), 4);
+            assert!(paired.iter().all(|(_, _, _, w)| w.is_finite()));
+
+            let (child_a, child_b) = crossover.crossover_pair(&mut rng, &parent_a, &parent_b);
+            let paired = [paired_weights(&child_a, &parent_a, &parent_b),
+                          paired_weights(&child_b, &parent_a, &parent_b)].concat();
+            approx::assert_relative_eq!(paired.iter().map(|(_, _, _, w)| w).sum::<f32>(), 4.0 * (1.0 + 3.0));
+        }
+    }
+}
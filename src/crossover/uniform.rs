@@ -53,6 +53,42 @@ impl CrossoverMethod for UniformCrossover {
         //     .collect()
         Chromosome::new(childs)
     }
+    //дополняющие дети: ген, взятый 1-м ребенком от одного родителя, 2-й берет от другого
+    fn crossover_pair(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome,
+        parent_b: &Chromosome,
+    ) -> (Chromosome, Chromosome) {
+        let parents =
+            if rng.gen_bool(0.5) {
+                (parent_a, parent_b)
+            } else {
+                (parent_b, parent_a)
+            };
+        let parent_map: HashMap<(usize, usize, usize), f32> = parents.1
+            .iter()
+            .map(|(w, l, o, i)| ((l, o, i), w))
+            .collect();
+        let mut sibling: HashMap<(usize, usize, usize), f32> = HashMap::new();
+        let child = parents.0
+            .iter()
+            .map(|(w1, l1, o1, i1)| match parent_map.get(&(l1, o1, i1)) {
+                Some(w2) => {
+                    let (c1, c2) = if rng.gen_bool(0.5) { (*w2, w1) } else { (w1, *w2) };
+                    sibling.insert((l1, o1, i1), c2);
+                    (c1, l1, o1, i1)
+                }
+                None => (w1, l1, o1, i1),
+            })
+            .collect();
+        //2-й ребенок: структура 2-го родителя, веса совпадающих генов - не доставшиеся 1-му
+        let sibling = parents.1
+            .iter()
+            .map(|(w, l, o, i)| (*sibling.get(&(l, o, i)).unwrap_or(&w), l, o, i))
+            .collect();
+        (child, sibling)
+    }
 }
 
 #[cfg(test)]
@@ -92,4 +128,18 @@ mod tests {
         assert_eq!(diff_a, 51);
         assert_eq!(diff_b, 51);
     }
+
+    #[test]
+    fn pair() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let parent_a: Chromosome = (1..=100).map(|n| (n as f32, 2, n, 0)).collect();
+        let parent_b: Chromosome = (1..=100).map(|n| (-(n as f32), 2, n, 0)).collect();
+
+        let (child_a, child_b) = UniformCrossover.crossover_pair(&mut rng, &parent_a, &parent_b);
+
+        //каждый ген родителей достался ровно одному ребенку
+        assert!(child_a.iter().zip(child_b.iter()).all(|((a, ..), (b, ..))| a == -b));
+        assert!(child_a.iter().any(|(w, ..)| w > 0.0));
+        assert!(child_a.iter().any(|(w, ..)| w < 0.0));
+    }
 }
@@ -0,0 +1,229 @@
+use crate::*;
+use std::cell::RefCell;
+
+/// Стратегия дифференциальной эволюции
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeStrategy {
+    Rand1Bin,//DE/rand/1/bin: база - случайный вектор
+    Best1Bin,//DE/best/1/bin: база - лучший вектор
+}
+
+//Дифференциальная эволюция весов хромосом при неизменной топологии сети.
+//Каждая особь популяции - пробный вектор (trial) для своего целевого вектора (target).
+//На шаге evolve оцененные пробные векторы заменяют свои целевые, если они не хуже,
+//и из целевых векторов создаются новые пробные: мутант base + F*(r1 - r2) и биномиальное
+//скрещивание с вероятностью CR. Как и GeneticAlgorithm::evolve, на вход подается
+//оцененная популяция, а возвращается новая популяция и статистика.
+//Целевые векторы меняются внутри evolve(&self), поэтому DifferentialEvolution
+//реализует Evolve и может храниться как Box<dyn Evolve<I>>.
+pub struct DifferentialEvolution {
+    strategy: DeStrategy,
+    f: f32,//дифференциальный вес F
+    cr: f32,//вероятность скрещивания CR
+    targets: RefCell<Vec<(Chromosome, f32)>>,//целевые векторы с их fitness
+}
+
+impl DifferentialEvolution {
+    pub fn new(strategy: DeStrategy, f: f32, cr: f32) -> Self {
+        Self::try_new(strategy, f, cr).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    pub fn try_new(strategy: DeStrategy, f: f32, cr: f32) -> Result<Self, Error> {
+        if !(f > 0.0 && f <= 2.0) {
+            return Err(Error::InvalidParameter { name: "f", value: f });
+        }
+        if !(0.0..=1.0).contains(&cr) {
+            return Err(Error::InvalidParameter { name: "cr", value: cr });
+        }
+        Ok(Self {
+            strategy,
+            f,
+            cr,
+            targets: RefCell::new(Vec::new()),
+        })
+    }
+    /// Целевые векторы (лучшие найденные хромосомы для каждого места в популяции)
+    pub fn targets(&self) -> Vec<(Chromosome, f32)> {
+        self.targets.borrow().clone()
+    }
+
+    pub fn evolve<I>(&self, rng: &mut dyn RngCore, population: &[I]) -> (Vec<I>, Statistics)
+    where
+        I: Individual,
+    {
+        self.try_evolve(rng, population).unwrap_or_else(|err| panic!("{err}"))
+    }
+    /// evolve без паники: для мутанта нужны 4 различных вектора, в популяции меньше 4 особей
+    /// - ошибка
+    pub fn try_evolve<I>(&self, rng: &mut dyn RngCore, population: &[I]) -> Result<(Vec<I>, Statistics), Error>
+    where
+        I: Individual,
+    {
+        if population.is_empty() {
+            return Err(Error::EmptyPopulation);
+        }
+        if population.len() < 4 {
+            return Err(Error::InvalidParameter { name: "population", value: population.len() as f32 });
+        }
+
+        //отбор: пробный вектор заменяет целевой, если он не хуже
+        let mut targets = self.targets.borrow_mut();
+        let mut ch_count: usize = 0;
+        if targets.len() != population.len() {//первый шаг
+            *targets = population.iter()
+                .map(|i| (i.chromosome().clone(), i.fitness()))
+                .collect();
+            ch_count = population.len();
+        } else {
+            for (target, trial) in targets.iter_mut().zip(population) {
+                if trial.fitness() >= target.1 {
+                    *target = (trial.chromosome().clone(), trial.fitness());
+                    ch_count += 1;
+                }
+            }
+        }
+        let best = targets.iter()
+            .enumerate()
+            .max_by(|a, b| a.1.1.partial_cmp(&b.1.1).unwrap_or(Ordering::Equal))
+            .map_or(0, |(j, _)| j);
+        let len = population.len();
+        let new_population = (0..len)
+            .map(|j| {
+                //случайные различные векторы, не совпадающие с целевым
+                let mut picked = vec![j];
+                let mut pick = |rng: &mut dyn RngCore| {
+                    let r = loop {
+                        let r = rng.gen_range(0..len);
+                        if !picked.contains(&r) { break r; }
+                    };
+                    picked.push(r);
+                    r
+                };
+                let base = match self.strategy {
+                    DeStrategy::Rand1Bin => pick(rng),
+                    DeStrategy::Best1Bin => best,
+                };
+                let (r1, r2) = (pick(rng), pick(rng));
+
+                let target = &targets[j].0;
+                let j_rand = rng.gen_range(0..target.len().max(1));//хотя бы один ген от мутанта
+                //гены векторов выравниваются с целевым как в кроссовере (align),
+                //входы первого слоя пары не имеют и не меняются
+                let [base, x, y] = [base, r1, r2].map(|k| {
+                    let vector = &targets[k].0;
+                    align(target, vector).into_iter().map(move |pair| pair.map(|n| vector[n].0))
+                });
+                let trial: Chromosome = target.iter()
+                    .zip(base.zip(x).zip(y))
+                    .enumerate()
+                    .map(|(n, ((w, l, o, i), ((b, x), y)))| {
+                        let mutant = b.zip(x).zip(y).map(|((b, x), y)| b + self.f * (x - y));
+                        match mutant {
+                            Some(m) if n == j_rand || rng.gen_bool(self.cr as _) => (m, l, o, i),
+                            _ => (w, l, o, i),
+                        }
+                    })
+                    .collect();
+                I::create(trial)
+            })
+            .collect();
+        let mut statistic = Statistics::new(population);
+        statistic.set_changed_count(ch_count);
+        Ok((new_population, statistic))
+    }
+}
+
+impl<I> Evolve<I> for DifferentialEvolution
+where
+    I: Individual,
+{
+    fn try_evolve(&self, rng: &mut dyn RngCore, population: &[I]) -> Result<(Vec<I>, Statistics), Error> {
+        DifferentialEvolution::try_evolve(self, rng, population)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    //fitness TestIndividual - сумма весов, у максимума нет предела
+    fn population(rng: &mut dyn RngCore) -> Vec<TestIndividual> {
+        (0..10)
+            .map(|_| TestIndividual::create((1..=5).map(|n| (rng.gen_range(-1.0..1.0), 2, n, 0)).collect()))
+            .collect()
+    }
+
+    #[test]
+    fn improves_fitness() {
+        for strategy in [DeStrategy::Rand1Bin, DeStrategy::Best1Bin] {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let de = DifferentialEvolution::new(strategy, 0.8, 0.9);
+            let mut population = population(&mut rng);
+
+            let (_, first) = de.evolve(&mut rng, &population);
+            let mut last = first.clone();
+            for _ in 0..30 {
+                let (new_population, statistic) = de.evolve(&mut rng, &population);
+                population = new_population;
+                last = statistic;
+            }
+
+            assert_eq!(first.changed_count(), 10);
+            assert!(last.max_fitness() > first.max_fitness() + 1.0);
+            //целевые векторы не ухудшаются
+            assert!(de.targets().iter().all(|(_, f)| *f >= first.min_fitness()));
+        }
+    }
+
+    #[test]
+    fn keeps_topology() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let de = DifferentialEvolution::new(DeStrategy::Rand1Bin, 0.5, 1.0);
+        let population = population(&mut rng);
+
+        let (new_population, _) = de.evolve(&mut rng, &population);
+
+        for (child, parent) in new_population.iter().zip(&population) {
+            let keys = |i: &TestIndividual| -> Vec<(usize, usize, usize)> {
+                i.chromosome().iter().map(|(_, l, o, i)| (l, o, i)).collect()
+            };
+            assert_eq!(keys(child), keys(parent));
+            assert_ne!(child, parent);
+        }
+    }
+
+    #[test]
+    fn aligns_by_position() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let de = DifferentialEvolution::new(DeStrategy::Rand1Bin, 0.5, 1.0);
+        //у фиктивных генов слоя 0 один ключ, но у одинаковых векторов мутант равен целевому
+        let genes = [(1.0,0,0,0), (2.0,0,0,0), (3.0,0,0,0)];
+        let population: Vec<TestIndividual> = (0..4)
+            .map(|_| TestIndividual::create(genes.iter().cloned().collect()))
+            .collect();
+
+        let (new_population, _) = de.evolve(&mut rng, &population);
+
+        assert_eq!(new_population, population);
+    }
+
+    #[test]
+    fn evolve_dyn() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let de: Box<dyn Evolve<TestIndividual>> = Box::new(DifferentialEvolution::new(DeStrategy::Best1Bin, 0.8, 0.9));
+        let mut population = population(&mut rng);
+
+        let (_, first) = de.evolve(&mut rng, &population);
+        for _ in 0..10 {
+            population = de.evolve(&mut rng, &population).0;
+        }
+        let (_, last) = de.evolve(&mut rng, &population);
+
+        //целевые векторы сохраняются между вызовами через &dyn Evolve
+        assert_eq!(first.changed_count(), 10);
+        assert!(last.max_fitness() > first.max_fitness());
+        assert!(de.try_evolve(&mut rng, &population[..3]).is_err());
+    }
+}
@@ -0,0 +1,28 @@
+use std::fmt;
+
+/// Ошибки библиотеки. Возвращаются try_* вариантами конструкторов и evolve вместо паники,
+/// чтобы долго работающая симуляция не падала на некорректной популяции или параметрах.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+    EmptyPopulation,//пустая популяция
+    InvalidParameter { name: &'static str, value: f32 },//параметр вне допустимого диапазона
+    InvalidFitness { index: usize },//fitness, по которому нельзя выбрать особь (бесконечный, fitness_vector другой длины)
+    MalformedChromosome { index: usize },//пустая хромосома или ген с номером слоя 0
+    InvalidBehaviour { index: usize },//вектор поведения другой длины
+    Config(String),//ошибка чтения или несовместимые методы в конфигурации
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EmptyPopulation => write!(f, "got an empty population"),
+            Self::InvalidParameter { name, value } => write!(f, "invalid {name}: {value}"),
+            Self::InvalidFitness { index } => write!(f, "invalid fitness of individual {index}"),
+            Self::MalformedChromosome { index } => write!(f, "malformed chromosome of individual {index}"),
+            Self::InvalidBehaviour { index } => write!(f, "invalid behaviour of individual {index}"),
+            Self::Config(message) => write!(f, "invalid configuration: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,263 @@
+use crate::*;
+use std::io::{self, Write};
+
+/// Запись родословной одной особи
+#[derive(Clone, Debug, PartialEq)]
+pub struct LineageRecord {
+    id: u64,
+    generation: usize,//поколение особи (начальная популяция - 0)
+    parents: Vec<u64>,//пусто у особей начальной популяции
+    crossover: Option<&'static str>,//имя метода кроссовера
+    mutation: Option<&'static str>,//имя метода мутации
+    structural_changes: Vec<StructuralChange>,
+}
+
+impl LineageRecord {
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    pub fn parents(&self) -> &[u64] {
+        &self.parents
+    }
+
+    pub fn crossover(&self) -> Option<&'static str> {
+        self.crossover
+    }
+
+    pub fn mutation(&self) -> Option<&'static str> {
+        self.mutation
+    }
+
+    pub fn structural_changes(&self) -> &[StructuralChange] {
+        &self.structural_changes
+    }
+}
+
+/// Родословная особей: у каждой созданной особи уникальный id, id родителей и примененные
+/// к ней операторы (кроссовер, мутация и изменения структуры Flex1Mutation).
+/// Особи популяции не хранят id, их id по индексам популяции возвращает ids() - для
+/// популяции, которую вернул последний evolve. Особи, поданные в следующий evolve,
+/// узнаются по генам, поэтому популяцию после оценки можно переупорядочить.
+/// Хранится не больше max_records последних записей (по умолчанию 100000).
+/// Включается в GeneticAlgorithm и GeneticFlexAlgorithm через with_genealogy.
+#[derive(Clone, Debug)]
+pub struct Genealogy {
+    records: Vec<LineageRecord>,//по возрастанию id без пропусков
+    max_records: usize,//макс. кол. хранимых записей, старые удаляются
+    next_id: u64,
+    ids: Vec<u64>,//id особей текущей популяции
+    fingerprints: Vec<u64>,//отпечатки генов особей текущей популяции
+    generation: usize,//номер текущего поколения
+}
+
+impl Default for Genealogy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Genealogy {
+    pub fn new() -> Self {
+        Self {
+            records: Vec::new(),
+            max_records: 100_000,
+            next_id: 1,
+            ids: Vec::new(),
+            fingerprints: Vec::new(),
+            generation: 0,
+        }
+    }
+    /// Макс. кол. хранимых записей: при переполнении в конце поколения удаляются самые
+    /// старые, и их особи пропадают из ancestors и write_edge_list
+    pub fn with_max_records(mut self, max_records: usize) -> Self {
+        self.max_records = max_records;
+        self
+    }
+    /// id особей текущей популяции по индексам
+    pub fn ids(&self) -> &[u64] {
+        &self.ids
+    }
+
+    pub fn records(&self) -> &[LineageRecord] {
+        &self.records
+    }
+
+    pub fn record(&self, id: u64) -> Option<&LineageRecord> {
+        let first = self.records.first()?.id;
+        id.checked_sub(first).and_then(|j| self.records.get(j as usize))
+    }
+    /// Все предки особи (без нее самой), начиная с ближайших
+    pub fn ancestors(&self, id: u64) -> Vec<u64> {
+        let mut ancestors: Vec<u64> = Vec::new();
+        let mut visited: HashSet<u64> = HashSet::new();
+        let mut queue: VecDeque<u64> = VecDeque::from([id]);
+        while let Some(next) = queue.pop_front() {
+            for parent in self.record(next).map_or(&[][..], |r| r.parents()) {
+                if visited.insert(*parent) {
+                    ancestors.push(*parent);
+                    queue.push_back(*parent);
+                }
+            }
+        }
+        ancestors
+    }
+    /// Вывод родословной списком ребер в CSV: родитель -> ребенок с операторами ребенка.
+    /// Структурные изменения перечисляются через ';'
+    pub fn write_edge_list<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writeln!(writer, "parent,child,generation,crossover,mutation,structural_changes")?;
+        for record in &self.records {
+            let changes: Vec<String> = record.structural_changes.iter().map(|c| c.to_string()).collect();
+            for parent in &record.parents {
+                writeln!(writer, "{},{},{},{},{},{}",
+                         parent,
+                         record.id,
+                         record.generation,
+                         record.crossover.unwrap_or(""),
+                         record.mutation.unwrap_or(""),
+                         changes.join(";"))?;
+            }
+        }
+        Ok(())
+    }
+    //начало поколения: особи узнаются по отпечаткам генов популяции, которую вернул прошлый
+    //evolve, остальные считаются начальными и получают id без родителей
+    pub(crate) fn begin_generation<'a>(&mut self, chromosomes: impl Iterator<Item = &'a Chromosome>) {
+        let mut known: HashMap<u64, VecDeque<u64>> = HashMap::new();
+        for (fingerprint, id) in self.fingerprints.iter().zip(&self.ids) {
+            known.entry(*fingerprint).or_default().push_back(*id);
+        }
+        let mut ids: Vec<u64> = Vec::new();
+        let mut fingerprints: Vec<u64> = Vec::new();
+        for chromosome in chromosomes {
+            let fingerprint = chromosome.fingerprint();
+            let id = known.get_mut(&fingerprint).and_then(|ids| ids.pop_front());
+            ids.push(id.unwrap_or_else(|| self.add(Vec::new(), None, None, self.generation)));
+            fingerprints.push(fingerprint);
+        }
+        self.ids = ids;
+        self.fingerprints = fingerprints;
+    }
+    //id родителей по их индексам в текущей популяции
+    pub(crate) fn parent_ids(&self, parents: (usize, usize)) -> Vec<u64> {
+        vec![self.ids[parents.0], self.ids[parents.1]]
+    }
+    //новая особь, она относится к следующему поколению
+    pub(crate) fn add_child(&mut self,
+                            parents: Vec<u64>,
+                            crossover: &'static str,
+                            mutation: &'static str,
+    ) -> u64 {
+        self.add(parents, Some(crossover), Some(mutation), self.generation + 1)
+    }
+
+    //оставшаяся особь, веса или структуру которой изменила мутация: новая особь
+    //следующего поколения с единственным родителем
+    pub(crate) fn add_mutant(&mut self, parent: u64, mutation: &'static str) -> u64 {
+        self.add(vec![parent], None, Some(mutation), self.generation + 1)
+    }
+
+    pub(crate) fn add_structural_changes(&mut self, id: u64, changes: Vec<StructuralChange>) {
+        let first = self.records.first().map_or(0, |r| r.id);
+        if let Some(record) = id.checked_sub(first).and_then(|j| self.records.get_mut(j as usize)) {
+            record.structural_changes.extend(changes);
+        }
+    }
+    //конец поколения: ids - id новой популяции, chromosomes - ее гены
+    pub(crate) fn end_generation<'a>(&mut self,
+                                     ids: Vec<u64>,
+                                     chromosomes: impl Iterator<Item = &'a Chromosome>,
+    ) {
+        self.ids = ids;
+        self.fingerprints = chromosomes.map(|c| c.fingerprint()).collect();
+        self.generation += 1;
+        let excess = self.records.len().saturating_sub(self.max_records);
+        self.records.drain(..excess);
+    }
+
+    fn add(&mut self,
+           parents: Vec<u64>,
+           crossover: Option<&'static str>,
+           mutation: Option<&'static str>,
+           generation: usize,
+    ) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.records.push(LineageRecord {
+            id,
+            generation,
+            parents,
+            crossover,
+            mutation,
+            structural_changes: Vec::new(),
+        });
+        id
+    }
+}
+
+//имя типа без пути модулей, имя оператора в родословной по умолчанию
+pub(crate) fn type_short_name<T: ?Sized>() -> &'static str {
+    let name = std::any::type_name::<T>();
+    let name = name.split('<').next().unwrap_or(name);
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chromosomes(weights: &[f32]) -> Vec<Chromosome> {
+        weights.iter().map(|w| Chromosome::new(vec![(*w, 2, 1, 0)])).collect()
+    }
+
+    #[test]
+    fn edge_list() {
+        let mut genealogy = Genealogy::new();
+        genealogy.begin_generation(chromosomes(&[1.0, 2.0]).iter());
+        assert_eq!(genealogy.ids(), &[1, 2]);
+
+        let a = genealogy.add_child(genealogy.parent_ids((0, 1)), "UniformCrossover", "Flex1Mutation");
+        genealogy.add_structural_changes(a, vec![StructuralChange::AddNeuron { layer: 2, neuron: 5 }]);
+        let b = genealogy.add_child(genealogy.parent_ids((1, 1)), "UniformCrossover", "Flex1Mutation");
+        genealogy.end_generation(vec![a, b], chromosomes(&[3.0, 4.0]).iter());
+        //та же популяция в другом порядке, новых id нет
+        genealogy.begin_generation(chromosomes(&[4.0, 3.0]).iter());
+        assert_eq!(genealogy.ids(), &[4, 3]);
+
+        let c = genealogy.add_child(vec![a, b], "UniformCrossover", "Flex1Mutation");
+        genealogy.end_generation(vec![c, b], chromosomes(&[5.0, 4.0]).iter());
+
+        assert_eq!(genealogy.record(c).unwrap().generation(), 2);
+        assert_eq!(genealogy.ancestors(c), vec![3, 4, 1, 2]);
+
+        let mut out = Vec::new();
+        genealogy.write_edge_list(&mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 7);
+        assert_eq!(lines[1], "1,3,1,UniformCrossover,Flex1Mutation,add_neuron(2:5)");
+        assert_eq!(lines[6], "4,5,2,UniformCrossover,Flex1Mutation,");
+
+        //неизвестная особь получает новый id без родителей
+        genealogy.begin_generation(chromosomes(&[5.0, 6.0]).iter());
+        assert_eq!(genealogy.ids(), &[5, 6]);
+        assert!(genealogy.record(6).unwrap().parents().is_empty());
+    }
+
+    #[test]
+    fn max_records() {
+        let mut genealogy = Genealogy::new().with_max_records(3);
+        genealogy.begin_generation(chromosomes(&[1.0, 2.0]).iter());
+        let a = genealogy.add_child(genealogy.parent_ids((0, 1)), "UniformCrossover", "GaussianMutation");
+        let b = genealogy.add_child(genealogy.parent_ids((0, 1)), "UniformCrossover", "GaussianMutation");
+        genealogy.end_generation(vec![a, b], chromosomes(&[3.0, 4.0]).iter());
+
+        assert_eq!(genealogy.records().iter().map(|r| r.id()).collect::<Vec<_>>(), vec![2, 3, 4]);
+        assert!(genealogy.record(1).is_none());
+        assert_eq!(genealogy.ancestors(a), vec![1, 2]);
+    }
+}
@@ -1,53 +1,306 @@
 use crate::*;
+use std::cell::{Ref, RefCell};
+
+/// Какие особи заменяются детьми в стационарном (steady-state) режиме
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Replacement {
+    Worst,//худшие по fitness
+    ReverseTournament { size: usize },//проигравшие обратного турнира из size особей
+}
+
+/// 1 шаг эволюции популяции I, общий для алгоритмов. Позволяет хранить алгоритм,
+/// собранный из конфигурации (AlgorithmConfig), как Box<dyn Evolve<I>>
+pub trait Evolve<I> {
+    fn try_evolve(&self, rng: &mut dyn RngCore, population: &[I]) -> Result<(Vec<I>, Statistics), Error>;
+
+    fn evolve(&self, rng: &mut dyn RngCore, population: &[I]) -> (Vec<I>, Statistics) {
+        self.try_evolve(rng, population).unwrap_or_else(|err| panic!("{err}"))
+    }
+}
 
 pub struct GeneticAlgorithm<S> {
-    sim_generation_length: usize,//длительность 1-го цикла перед обучением
     selection_method: S,
     crossover_method: Box<dyn CrossoverMethod>,
     mutation_method: Box<dyn MutationMethod>,
+    child_pairs: bool,//от пары родителей берутся оба ребенка (crossover_pair)
+    elitism: usize,//сколько лучших особей переходит в новое поколение без изменений
+    genealogy: Option<RefCell<Genealogy>>,//родословная, если ведется
+    children: RefCell<Vec<u64>>,//отпечатки генов детей последней новой популяции, в порядке создания
 }
 
 
-impl<S> GeneticAlgorithm<S>
-where
-    S: SelectionMethod,
-{
+impl<S> GeneticAlgorithm<S> {
+    /// _sim_generation_length не используется (время жизни есть только во Flex режиме),
+    /// параметр оставлен для совместимости. Удобнее создавать через GeneticAlgorithmBuilder
     pub fn new(
-        sim_generation_length: usize,
+        _sim_generation_length: usize,
         selection_method: S,
         crossover_method: impl CrossoverMethod + 'static,
         mutation_method: impl MutationMethod + 'static,
+    ) -> Self {
+        Self::from_parts(selection_method, Box::new(crossover_method), Box::new(mutation_method))
+    }
+
+    pub(crate) fn from_parts(
+        selection_method: S,
+        crossover_method: Box<dyn CrossoverMethod>,
+        mutation_method: Box<dyn MutationMethod>,
     ) -> Self {
         Self {
-            sim_generation_length,//для генерирования life_time
             selection_method,
-            crossover_method: Box::new(crossover_method),
-            mutation_method: Box::new(mutation_method),
+            crossover_method,
+            mutation_method,
+            child_pairs: false,
+            elitism: 0,
+            genealogy: None,
+            children: RefCell::new(Vec::new()),
         }
     }
+    /// Сколько лучших особей переходит в новое поколение без изменений (в steady-state режиме
+    /// они не заменяются)
+    pub fn with_elitism(mut self, elitism: usize) -> Self {
+        self.elitism = elitism;
+        self
+    }
+    /// Заполнять популяцию обоими детьми каждой пары родителей (CrossoverMethod::crossover_pair).
+    /// При нечетном размере популяции второй ребенок последней пары отбрасывается.
+    pub fn with_child_pairs(mut self, child_pairs: bool) -> Self {
+        self.child_pairs = child_pairs;
+        self
+    }
+    /// Вести родословную особей (Genealogy)
+    pub fn with_genealogy(mut self, enabled: bool) -> Self {
+        self.genealogy = enabled.then(|| RefCell::new(Genealogy::new()));
+        self
+    }
+    /// Вести родословную, хранящую не больше max_records последних записей
+    pub fn with_genealogy_max_records(mut self, max_records: usize) -> Self {
+        self.genealogy = Some(RefCell::new(Genealogy::new().with_max_records(max_records)));
+        self
+    }
+
+    pub fn genealogy(&self) -> Option<Ref<'_, Genealogy>> {
+        self.genealogy.as_ref().map(|genealogy| genealogy.borrow())
+    }
 
     pub fn evolve<I>(&self, rng: &mut dyn RngCore, population: &[I]) -> (Vec<I>, Statistics)
     where
+        S: SelectionMethodDyn<I>,
+        I: Individual,
+    {
+        self.try_evolve(rng, population).unwrap_or_else(|err| panic!("{err}"))
+    }
+    /// evolve без паники: ошибка для пустой популяции или популяции, из которой нельзя выбрать
+    pub fn try_evolve<I>(&self, rng: &mut dyn RngCore, population: &[I]) -> Result<(Vec<I>, Statistics), Error>
+    where
+        S: SelectionMethodDyn<I>,
         I: Individual,
     {
-        assert!(!population.is_empty());
+        if population.is_empty() {
+            return Err(Error::EmptyPopulation);
+        }
+        if self.elitism > population.len() {
+            return Err(Error::InvalidParameter { name: "elitism", value: self.elitism as f32 });
+        }
+        let operators = self.begin_operators(population);
+        let _generation = SelectionGeneration::begin(&self.selection_method, population);
+        let mut genealogy = self.genealogy.as_ref().map(|genealogy| genealogy.borrow_mut());
+        if let Some(genealogy) = genealogy.as_mut() {
+            genealogy.begin_generation(population.iter().map(|i| i.chromosome()));
+        }
+        let mut ids: Vec<u64> = Vec::new();//id новой популяции для родословной
 
-        let new_population = (0..population.len())
-            .map(|_| {
-                let parent = self.selection_method.select(rng, population);
-                let parent_a = parent.0.chromosome();
-                let parent_b = parent.1.chromosome();
+        //элита переходит в новое поколение без изменений
+        let mut new_population: Vec<I> = Vec::with_capacity(population.len() + 1);
+        for j in self.elite(population) {
+            new_population.push(I::create(population[j].chromosome().clone()));
+            if let Some(genealogy) = genealogy.as_ref() {
+                ids.push(genealogy.ids()[j]);
+            }
+        }
+
+        while new_population.len() < population.len() {
+            let (a, b) = self.selection_method.try_select_indices_dyn(rng, population)?;
+            let parent = (&population[a], &population[b]);
+            let parent_a = parent.0.chromosome();
+            let parent_b = parent.1.chromosome();
+
+            if self.child_pairs {
+                let ((mut child_a, mut child_b), crossover) = self.crossover_method.crossover_pair_named(
+                    rng, parent_a, parent.0.fitness(), parent_b, parent.1.fitness());
+
+                let mutation_a = self.mutation_method.mutate_named(rng, &mut child_a);
+                let mutation_b = self.mutation_method.mutate_named(rng, &mut child_b);
 
-                let mut child = self.crossover_method.crossover(rng, parent_a, parent_b);
+                if let Some(genealogy) = genealogy.as_mut() {
+                    for mutation in [mutation_a, mutation_b] {
+                        let parents = genealogy.parent_ids((a, b));
+                        ids.push(genealogy.add_child(parents, crossover, mutation));
+                    }
+                }
+                new_population.push(I::create(child_a));
+                new_population.push(I::create(child_b));
+            } else {
+                let (mut child, crossover) = self.crossover_method.crossover_named(
+                    rng, parent_a, parent.0.fitness(), parent_b, parent.1.fitness());
 
-                self.mutation_method.mutate(rng, &mut child);
+                let mutation = self.mutation_method.mutate_named(rng, &mut child);
 
-                I::create(child)
-            })
+                if let Some(genealogy) = genealogy.as_mut() {
+                    let parents = genealogy.parent_ids((a, b));
+                    ids.push(genealogy.add_child(parents, crossover, mutation));
+                }
+                new_population.push(I::create(child));
+            }
+        }
+        new_population.truncate(population.len());//размер популяции не меняется
+        if let Some(genealogy) = genealogy.as_mut() {
+            ids.truncate(population.len());
+            genealogy.end_generation(ids, new_population.iter().map(|i| i.chromosome()));
+        }
+        *self.children.borrow_mut() = new_population[self.elitism..].iter()
+            .map(|i| i.chromosome().fingerprint())
             .collect();
+        operators.finish();
         let mut statistic = Statistics::new(population);
-        statistic.set_changed_count(population.len());
-        (new_population, statistic)
+        statistic.set_changed_count(population.len() - self.elitism);
+        Ok((new_population, statistic))
+    }
+    /// Стационарный (steady-state) шаг: создается только children детей, они заменяют
+    /// особей по методу replacement, остальная популяция не меняется (вместе с fitness).
+    /// Удобно для симуляции в реальном времени, когда нельзя останавливать всю стаю.
+    pub fn evolve_steady_state<I>(&self,
+                                  rng: &mut dyn RngCore,
+                                  population: &[I],
+                                  children: usize,
+                                  replacement: Replacement,
+    ) -> (Vec<I>, Statistics)
+    where
+        S: SelectionMethodDyn<I>,
+        I: Individual + Clone,
+    {
+        self.try_evolve_steady_state(rng, population, children, replacement)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+    /// evolve_steady_state без паники
+    pub fn try_evolve_steady_state<I>(&self,
+                                      rng: &mut dyn RngCore,
+                                      population: &[I],
+                                      children: usize,
+                                      replacement: Replacement,
+    ) -> Result<(Vec<I>, Statistics), Error>
+    where
+        S: SelectionMethodDyn<I>,
+        I: Individual + Clone,
+    {
+        if population.is_empty() {
+            return Err(Error::EmptyPopulation);
+        }
+        if children + self.elitism > population.len() {//элита не заменяется
+            return Err(Error::InvalidParameter { name: "children", value: children as f32 });
+        }
+        if let Replacement::ReverseTournament { size: 0 } = replacement {
+            return Err(Error::InvalidParameter { name: "size", value: 0.0 });
+        }
+        let operators = self.begin_operators(population);
+        let _generation = SelectionGeneration::begin(&self.selection_method, population);
+        let mut genealogy = self.genealogy.as_ref().map(|genealogy| genealogy.borrow_mut());
+        if let Some(genealogy) = genealogy.as_mut() {
+            genealogy.begin_generation(population.iter().map(|i| i.chromosome()));
+        }
+        //id новой популяции для родословной, не замененные особи сохраняют свои
+        let mut ids: Vec<u64> = genealogy.as_ref().map_or(Vec::new(), |g| g.ids().to_vec());
+
+        //индексы особей, которые можно заменить (кроме элиты)
+        let elite = self.elite(population);
+        let candidates: Vec<usize> = (0..population.len()).filter(|j| !elite.contains(j)).collect();
+        //индексы заменяемых особей
+        let replaced: Vec<usize> = match replacement {
+            Replacement::Worst => {
+                let mut order: Vec<usize> = candidates;
+                order.sort_by(|a, b| {
+                    population[*a].fitness()
+                        .partial_cmp(&population[*b].fitness())
+                        .unwrap_or(Ordering::Equal)
+                });
+                order.truncate(children);
+                order
+            }
+            Replacement::ReverseTournament { size } => {
+                let mut candidates: Vec<usize> = candidates;
+                let mut replaced: Vec<usize> = Vec::with_capacity(children);
+                for _ in 0..children {
+                    //худший из size случайных еще не замененных особей
+                    let loser = (0..size)
+                        .map(|_| rng.gen_range(0..candidates.len()))
+                        .min_by(|a, b| {
+                            population[candidates[*a]].fitness()
+                                .partial_cmp(&population[candidates[*b]].fitness())
+                                .unwrap_or(Ordering::Equal)
+                        })
+                        .unwrap();
+                    replaced.push(candidates.swap_remove(loser));
+                }
+                replaced
+            }
+        };
+
+        let mut new_population: Vec<I> = population.to_vec();
+        for j in replaced.iter().copied() {
+            let (a, b) = self.selection_method.try_select_indices_dyn(rng, population)?;
+            let parent = (&population[a], &population[b]);
+            let parent_a = parent.0.chromosome();
+            let parent_b = parent.1.chromosome();
+
+            let (mut child, crossover) = self.crossover_method.crossover_named(
+                rng, parent_a, parent.0.fitness(), parent_b, parent.1.fitness());
+
+            let mutation = self.mutation_method.mutate_named(rng, &mut child);
+
+            if let Some(genealogy) = genealogy.as_mut() {
+                let parents = genealogy.parent_ids((a, b));
+                ids[j] = genealogy.add_child(parents, crossover, mutation);
+            }
+            new_population[j] = I::create(child);
+        }
+        if let Some(genealogy) = genealogy.as_mut() {
+            genealogy.end_generation(ids, new_population.iter().map(|i| i.chromosome()));
+        }
+        *self.children.borrow_mut() = replaced.iter()
+            .map(|j| new_population[*j].chromosome().fingerprint())
+            .collect();
+        operators.finish();
+        let mut statistic = Statistics::new(population);
+        statistic.set_changed_count(children);
+        Ok((new_population, statistic))
+    }
+    //пригодность детей, созданных прошлым evolve, передается операторам (AdaptiveOperators).
+    //Дети ищутся в популяции по генам, если кого-то нет - журналы операторов очищаются
+    fn begin_operators<I>(&self, population: &[I]) -> OperatorsGeneration<'_>
+    where
+        I: Individual,
+    {
+        let children = self.children.take();
+        OperatorsGeneration::begin(
+            self.crossover_method.as_ref(), Some(self.mutation_method.as_ref()), &children, population)
+    }
+    //индексы elitism лучших особей популяции
+    fn elite<I>(&self, population: &[I]) -> Vec<usize>
+    where
+        S: SelectionMethodDyn<I>,
+        I: Individual,
+    {
+        if self.elitism == 0 {
+            return Vec::new();
+        }
+        let mut order: Vec<usize> = (0..population.len()).collect();
+        order.sort_by(|a, b| {
+            population[*b].fitness()
+                .partial_cmp(&population[*a].fitness())
+                .unwrap_or(Ordering::Equal)
+        });
+        order.truncate(self.elitism);
+        order
     }
 
     // pub fn evolve_1<I>(&self, rng: &mut dyn RngCore, population: &[I]) -> (Vec<I>, Statistics)
@@ -100,4 +353,14 @@ where
     //     statistic.set_changed_count(ch_count);
     //     (new_population, statistic)
     // }
-}
\ No newline at end of file
+}
+
+impl<S, I> Evolve<I> for GeneticAlgorithm<S>
+where
+    S: SelectionMethodDyn<I>,
+    I: Individual,
+{
+    fn try_evolve(&self, rng: &mut dyn RngCore, population: &[I]) -> Result<(Vec<I>, Statistics), Error> {
+        GeneticAlgorithm::try_evolve(self, rng, population)
+    }
+}
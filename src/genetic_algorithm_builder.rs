@@ -0,0 +1,186 @@
+use crate::*;
+
+//Построитель GeneticAlgorithm и GeneticFlexAlgorithm вместо позиционных new(...).
+//По умолчанию: RouletteWheelSelection, UniformCrossover и GaussianMutation::new(0.01, 0.3),
+//без элитизма и масштабирования fitness. Параметры проверяются в build() и build_flex().
+//sim_generation_length нужен только Flex режиму (время жизни 1..=sim_generation_length/500),
+//поэтому build() его не принимает, а build_flex() без него не строит алгоритм.
+pub struct GeneticAlgorithmBuilder<S> {
+    selection_method: S,
+    crossover_method: Box<dyn CrossoverMethod>,
+    mutation_method: Box<dyn MutationMethod>,
+    sim_generation_length: Option<usize>,//длительность 1-го цикла, только для Flex
+    elitism: usize,
+    child_pairs: bool,
+    genealogy: bool,
+}
+
+impl GeneticAlgorithmBuilder<RouletteWheelSelection> {
+    pub fn new() -> Self {
+        Self {
+            selection_method: RouletteWheelSelection,
+            crossover_method: Box::new(UniformCrossover),
+            mutation_method: Box::new(GaussianMutation::new(0.01, 0.3)),
+            sim_generation_length: None,
+            elitism: 0,
+            child_pairs: false,
+            genealogy: false,
+        }
+    }
+}
+
+impl Default for GeneticAlgorithmBuilder<RouletteWheelSelection> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> GeneticAlgorithmBuilder<S> {
+    /// Селекция, в том числе выбранная во время работы Box<dyn SelectionMethodDyn<I>>
+    pub fn selection<S2>(self, selection_method: S2) -> GeneticAlgorithmBuilder<S2> {
+        GeneticAlgorithmBuilder {
+            selection_method,
+            crossover_method: self.crossover_method,
+            mutation_method: self.mutation_method,
+            sim_generation_length: self.sim_generation_length,
+            elitism: self.elitism,
+            child_pairs: self.child_pairs,
+            genealogy: self.genealogy,
+        }
+    }
+    /// Масштабирование fitness перед выбранной селекцией (ScaledSelection)
+    pub fn scaling<F>(self, scaling: F) -> GeneticAlgorithmBuilder<ScaledSelection<S, F>>
+    where
+        S: FitnessSelectionMethod,
+        F: FitnessScaling,
+    {
+        let selection_method = ScaledSelection::new(self.selection_method, scaling);
+        GeneticAlgorithmBuilder {
+            selection_method,
+            crossover_method: self.crossover_method,
+            mutation_method: self.mutation_method,
+            sim_generation_length: self.sim_generation_length,
+            elitism: self.elitism,
+            child_pairs: self.child_pairs,
+            genealogy: self.genealogy,
+        }
+    }
+
+    pub fn crossover(mut self, crossover_method: impl CrossoverMethod + 'static) -> Self {
+        self.crossover_method = Box::new(crossover_method);
+        self
+    }
+    /// Мутация для GeneticAlgorithm, в build_flex мутация Flex передается отдельно
+    pub fn mutation(mut self, mutation_method: impl MutationMethod + 'static) -> Self {
+        self.mutation_method = Box::new(mutation_method);
+        self
+    }
+    /// Длительность 1-го цикла симуляции перед обучением, только для build_flex (не меньше 500)
+    pub fn sim_generation_length(mut self, sim_generation_length: usize) -> Self {
+        self.sim_generation_length = Some(sim_generation_length);
+        self
+    }
+    /// Сколько лучших особей переходит в новое поколение без изменений, только для build
+    pub fn elitism(mut self, elitism: usize) -> Self {
+        self.elitism = elitism;
+        self
+    }
+    /// GeneticAlgorithm::with_child_pairs, только для build
+    pub fn child_pairs(mut self, child_pairs: bool) -> Self {
+        self.child_pairs = child_pairs;
+        self
+    }
+
+    pub fn genealogy(mut self, genealogy: bool) -> Self {
+        self.genealogy = genealogy;
+        self
+    }
+
+    pub fn build(self) -> Result<GeneticAlgorithm<S>, Error> {
+        if let Some(sim_generation_length) = self.sim_generation_length {
+            return Err(Error::InvalidParameter {
+                name: "sim_generation_length",
+                value: sim_generation_length as f32,
+            });
+        }
+        Ok(GeneticAlgorithm::from_parts(self.selection_method, self.crossover_method, self.mutation_method)
+            .with_elitism(self.elitism)
+            .with_child_pairs(self.child_pairs)
+            .with_genealogy(self.genealogy))
+    }
+
+    pub fn build_flex<M>(self, mutation_method: M) -> Result<GeneticFlexAlgorithm<S, M>, Error> {
+        //во Flex режиме особи живут по life_time, элитизма и пар детей нет
+        if self.elitism > 0 {
+            return Err(Error::InvalidParameter { name: "elitism", value: self.elitism as f32 });
+        }
+        if self.child_pairs {
+            return Err(Error::InvalidParameter { name: "child_pairs", value: 1.0 });
+        }
+        let sim_generation_length = self.sim_generation_length.ok_or(Error::InvalidParameter {
+            name: "sim_generation_length",
+            value: 0.0,
+        })?;
+        Ok(GeneticFlexAlgorithm::try_from_parts(
+            sim_generation_length, self.selection_method, self.crossover_method, mutation_method)?
+            .with_genealogy(self.genealogy))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn individual(weights: &[f32]) -> TestIndividual {
+        TestIndividual::create(weights.iter().enumerate().map(|(n, w)| (*w, 2, n + 1, 0)).collect())
+    }
+
+    #[test]
+    fn validation() {
+        assert!(GeneticAlgorithmBuilder::new().build().is_ok());
+        assert!(GeneticAlgorithmBuilder::new().sim_generation_length(2500).build().is_err());
+        assert!(GeneticAlgorithmBuilder::new().build_flex(Flex1Mutation::new(0.5, 0.5, 9)).is_err());
+        assert!(GeneticAlgorithmBuilder::new()
+            .sim_generation_length(100)
+            .build_flex(Flex1Mutation::new(0.5, 0.5, 9))
+            .is_err());
+        assert!(GeneticAlgorithmBuilder::new()
+            .sim_generation_length(2500)
+            .elitism(1)
+            .build_flex(Flex1Mutation::new(0.5, 0.5, 9))
+            .is_err());
+        assert!(GeneticAlgorithmBuilder::new()
+            .sim_generation_length(2500)
+            .genealogy(true)
+            .build_flex(Flex1Mutation::new(0.5, 0.5, 9))
+            .is_ok());
+    }
+
+    #[test]
+    fn elitism_and_scaling() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let ga = GeneticAlgorithmBuilder::new()
+            .scaling(RankScaling)
+            .mutation(GaussianMutation::new(1.0, 0.5))
+            .elitism(2)
+            .build()
+            .unwrap();
+        let population = vec![
+            individual(&[1.0, 0.0]),
+            individual(&[4.0, 1.0]),
+            individual(&[0.5, 0.5]),
+            individual(&[3.0, 3.0]),
+        ];
+
+        let (new_population, statistic) = ga.evolve(&mut rng, &population);
+
+        assert_eq!(new_population.len(), 4);
+        assert_eq!(statistic.changed_count(), 2);
+        //лучшие особи в начале новой популяции без изменений
+        assert_eq!(new_population[0], population[3]);
+        assert_eq!(new_population[1], population[1]);
+        assert!(ga.try_evolve(&mut rng, &population[..1]).is_err());
+    }
+}
@@ -1,4 +1,5 @@
 use crate::*;
+use std::cell::{Ref, RefCell};
 //Набор алгоритмов для 1 шага репродуктивного плана Flex.
 //В этом плане изменяются веса и структура НС.
 //В основном это происходит в методе mutation.
@@ -9,32 +10,105 @@ pub struct GeneticFlexAlgorithm<S,M> {
     selection_method: S,
     crossover_method: Box<dyn CrossoverMethod>,
     mutation_method: M,
+    genealogy: Option<RefCell<Genealogy>>,//родословная, если ведется
+    children: RefCell<Vec<u64>>,//отпечатки генов детей кроссовера последней новой популяции
 }
 
-impl<S,M> GeneticFlexAlgorithm<S,M>
-where
-    S: SelectionMethod,
-    M: MutationMethodFlex,
-{
+impl<S,M> GeneticFlexAlgorithm<S,M> {
     pub fn new(
         sim_generation_length: usize,
         selection_method: S,
         crossover_method: impl CrossoverMethod + 'static,
         mutation_method: M,
+    ) -> Self {
+        Self::from_parts(sim_generation_length, selection_method, Box::new(crossover_method), mutation_method)
+    }
+    /// new с проверкой sim_generation_length >= 500 (иначе try_evolve вернет ошибку)
+    pub fn try_new(
+        sim_generation_length: usize,
+        selection_method: S,
+        crossover_method: impl CrossoverMethod + 'static,
+        mutation_method: M,
+    ) -> Result<Self, Error> {
+        Self::try_from_parts(sim_generation_length, selection_method, Box::new(crossover_method), mutation_method)
+    }
+
+    pub(crate) fn try_from_parts(
+        sim_generation_length: usize,
+        selection_method: S,
+        crossover_method: Box<dyn CrossoverMethod>,
+        mutation_method: M,
+    ) -> Result<Self, Error> {
+        check_sim_generation_length(sim_generation_length)?;
+        Ok(Self::from_parts(sim_generation_length, selection_method, crossover_method, mutation_method))
+    }
+
+    fn from_parts(
+        sim_generation_length: usize,
+        selection_method: S,
+        crossover_method: Box<dyn CrossoverMethod>,
+        mutation_method: M,
     ) -> Self {
         Self {
             sim_generation_length,//для генерирования life_time
             selection_method,
-            crossover_method: Box::new(crossover_method),
+            crossover_method,
             mutation_method,
+            genealogy: None,
+            children: RefCell::new(Vec::new()),
         }
     }
+    /// Вести родословную особей (Genealogy), в том числе изменения структуры при мутации
+    pub fn with_genealogy(mut self, enabled: bool) -> Self {
+        self.genealogy = enabled.then(|| RefCell::new(Genealogy::new()));
+        self
+    }
+    /// Вести родословную, хранящую не больше max_records последних записей
+    pub fn with_genealogy_max_records(mut self, max_records: usize) -> Self {
+        self.genealogy = Some(RefCell::new(Genealogy::new().with_max_records(max_records)));
+        self
+    }
+
+    pub fn genealogy(&self) -> Option<Ref<'_, Genealogy>> {
+        self.genealogy.as_ref().map(|genealogy| genealogy.borrow())
+    }
     //1 шаг репродуктивного плана
     pub fn evolve<I>(&self, rng: &mut dyn RngCore, population: &[I]) -> (Vec<I>, Statistics)
     where
+        S: SelectionMethodDyn<I>,
+        M: MutationMethodFlexDyn<I>,
+        I: IndividualFlex + Clone,
+    {
+        self.try_evolve(rng, population).unwrap_or_else(|err| panic!("{err}"))
+    }
+    /// evolve без паники: ошибка для пустой популяции, популяции, из которой нельзя выбрать,
+    /// хромосомы, у которой нет представления сети (пустая или со слоем 0),
+    /// или sim_generation_length < 500
+    pub fn try_evolve<I>(&self, rng: &mut dyn RngCore, population: &[I]) -> Result<(Vec<I>, Statistics), Error>
+    where
+        S: SelectionMethodDyn<I>,
+        M: MutationMethodFlexDyn<I>,
         I: IndividualFlex + Clone,
     {
-        assert!(!population.is_empty());
+        if population.is_empty() {
+            return Err(Error::EmptyPopulation);
+        }
+        check_sim_generation_length(self.sim_generation_length)?;
+        if let Some(index) = population.iter().position(|i| {
+            i.chromosome().is_empty() || i.chromosome().iter().any(|(_, layer_num, _, _)| layer_num == 0)
+        }) {
+            return Err(Error::MalformedChromosome { index });
+        }
+        //пригодность детей прошлого evolve (найденных по генам) - кроссоверу (AdaptiveOperators)
+        let operators = OperatorsGeneration::begin(
+            self.crossover_method.as_ref(), None, &self.children.take(), population);
+        let _generation = SelectionGeneration::begin(&self.selection_method, population);
+        let mut genealogy = self.genealogy.as_ref().map(|genealogy| genealogy.borrow_mut());
+        if let Some(genealogy) = genealogy.as_mut() {
+            genealogy.begin_generation(population.iter().map(|i| i.chromosome()));
+        }
+        //id новой популяции для родословной, оставшиеся особи сохраняют свои
+        let mut ids: Vec<u64> = genealogy.as_ref().map_or(Vec::new(), |g| g.ids().to_vec());
         let mut statistic = Statistics::new(population);
         let range = statistic.max_fitness() - statistic.min_fitness();
         // let q1 = statistic.min_fitness() + range / 3.0f32;    // ~1/3 от диапазона
@@ -47,39 +121,47 @@ where
         //при этом "хорошие" птички сохраняют свою жизнь дольше
         let mut new_population: Vec<I> = Vec::new();
         let mut ch_count: usize = 0;
+        let mut children: Vec<usize> = Vec::new();
         for (j, parent) in population.iter().enumerate() {
             let fitness = parent.fitness();
             let mut life_time = parent.life_time();//life_time сколько осталось жить птичке
-            let mut mut_force: usize = 0;//сила мутации
+            let mut_force: usize;//сила мутации
             if fitness < q1 {        // ~первая ~1/4 от диапазона
-                life_time -= 1;//под замену как только станет 0
+                life_time = life_time.saturating_sub(1);//под замену как только станет 0
                 mut_force = 3;//сильная мутация - веса + удаление/добавление слоя
             } else if fitness < q2 { // ~вторая 1/4 от диапазона
-                life_time -= 1;//life_time -= 1,
+                life_time = life_time.saturating_sub(1);//life_time -= 1,
                 mut_force = 2;//средняя мутация - веса + удаление/добавление нейронов в слой
             } else if fitness < q3 { // ~третья 1/4 от диапазона
-                life_time -= 1;//под замену как только станет 0
+                life_time = life_time.saturating_sub(1);//под замену как только станет 0
                 mut_force = 1;//слабая мутация - только веса
             } else {                 // ~четвертая 1/4 от диапазона
                 //Время жизни не меняется, т.к. Individual хорошо приспособлена
                 mut_force = 0;//нет мутации
             }
             if life_time == 0 { //под замену
-                let parents = self.selection_method.select(rng, population);
+                let (a, b) = self.selection_method.try_select_indices_dyn(rng, population)?;
+                let parents = (&population[a], &population[b]);
                 let parent_a = parents.0.chromosome();
                 let parent_b = parents.1.chromosome();
                 // //сам оцениваемый родитель
                 // let parent_a = parent.chromosome();
 
-                let child_chromosome =
-                    self.crossover_method.crossover(rng, parent_a, parent_b);
+                let (child_chromosome, crossover) = self.crossover_method.crossover_named(
+                    rng, parent_a, parents.0.fitness(), parent_b, parents.1.fitness());
 
+                if let Some(genealogy) = genealogy.as_mut() {
+                    let parent_ids = genealogy.parent_ids((a, b));
+                    ids[j] = genealogy.add_child(
+                        parent_ids, crossover, self.mutation_method.name_dyn());
+                }
                 life_time = rng.gen_range(1..=self.sim_generation_length/500);
                 let new_individual = <I as IndividualFlex>::create(child_chromosome,
                                                                     life_time,
                                                                     true,
                                                                     mut_force);
                 new_population.push(new_individual);
+                children.push(j);
                 ch_count += 1;
             } else {
                 let child_chromosome = population[j].chromosome().clone();
@@ -91,8 +173,48 @@ where
             }
         };
         //мутация структуры (2,3 и changed) и весов (1,2,3) у всей новой популяции
-        self.mutation_method.mutate(rng, &mut new_population);
+        let before: Vec<u64> = match genealogy {
+            Some(_) => new_population.iter().map(|i| i.chromosome().fingerprint()).collect(),
+            None => Vec::new(),
+        };
+        let changes = self.mutation_method.mutate_traced_dyn(rng, &mut new_population);
+        if let Some(genealogy) = genealogy.as_mut() {
+            for (j, changes) in changes.into_iter().enumerate() {
+                //измененная мутацией оставшаяся особь - новая особь
+                if children.binary_search(&j).is_err() && new_population[j].chromosome().fingerprint() != before[j] {
+                    ids[j] = genealogy.add_mutant(ids[j], self.mutation_method.name_dyn());
+                }
+                genealogy.add_structural_changes(ids[j], changes);
+            }
+            genealogy.end_generation(ids, new_population.iter().map(|i| i.chromosome()));
+        }
+        *self.children.borrow_mut() = children.iter()
+            .map(|j| new_population[*j].chromosome().fingerprint())
+            .collect();
+        operators.finish();
         statistic.set_changed_count(ch_count);
-        (new_population, statistic)
+        Ok((new_population, statistic))
+    }
+}
+
+//время жизни новых особей выбирается из 1..=sim_generation_length/500
+fn check_sim_generation_length(sim_generation_length: usize) -> Result<(), Error> {
+    if sim_generation_length < 500 {
+        return Err(Error::InvalidParameter {
+            name: "sim_generation_length",
+            value: sim_generation_length as f32,
+        });
+    }
+    Ok(())
+}
+
+impl<S, M, I> Evolve<I> for GeneticFlexAlgorithm<S, M>
+where
+    S: SelectionMethodDyn<I>,
+    M: MutationMethodFlexDyn<I>,
+    I: IndividualFlex + Clone,
+{
+    fn try_evolve(&self, rng: &mut dyn RngCore, population: &[I]) -> Result<(Vec<I>, Statistics), Error> {
+        GeneticFlexAlgorithm::try_evolve(self, rng, population)
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,167 @@
+use crate::*;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// Особь зала славы: хромосома, ее fitness и поколение, в котором она найдена
+#[derive(Clone, Debug)]
+pub struct HallOfFameEntry {
+    chromosome: Chromosome,
+    fitness: f32,
+    generation: usize,
+    hash: u64,//хэш генов для быстрого поиска повторов
+}
+
+impl HallOfFameEntry {
+    pub fn chromosome(&self) -> &Chromosome {
+        &self.chromosome
+    }
+
+    pub fn fitness(&self) -> f32 {
+        self.fitness
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+}
+
+/// Зал славы: capacity лучших различных особей за все поколения, по убыванию fitness.
+/// GeneticAlgorithm и GeneticFlexAlgorithm не помнят прошлые поколения, а Statistics
+/// показывает max_fitness только текущего, поэтому лучшие особи сохраняются здесь.
+/// Одинаковые хромосомы (те же гены и веса) хранятся один раз, с поколением, в котором
+/// такая хромосома встретилась впервые.
+#[derive(Clone, Debug)]
+pub struct HallOfFame {
+    capacity: usize,
+    entries: Vec<HallOfFameEntry>,
+    next_generation: usize,//номер следующего поколения для update
+}
+
+impl HallOfFame {
+    pub fn new(capacity: usize) -> Self {
+        Self::try_new(capacity).unwrap_or_else(|err| panic!("{err}"))
+    }
+    /// new без паники: нужна capacity > 0
+    pub fn try_new(capacity: usize) -> Result<Self, Error> {
+        if capacity == 0 {
+            return Err(Error::InvalidParameter { name: "capacity", value: 0.0 });
+        }
+        Ok(Self {
+            capacity,
+            entries: Vec::with_capacity(capacity),
+            next_generation: 0,
+        })
+    }
+    /// Учет оцененной популяции следующего поколения (нумерация с 0),
+    /// возвращает кол. особей, попавших в зал славы
+    pub fn update<I>(&mut self, population: &[I]) -> usize
+    where
+        I: Individual,
+    {
+        self.update_generation(self.next_generation, population)
+    }
+    /// Учет оцененной популяции поколения с явным номером
+    pub fn update_generation<I>(&mut self, generation: usize, population: &[I]) -> usize
+    where
+        I: Individual,
+    {
+        self.next_generation = generation + 1;
+        population.iter()
+            .filter(|individual| self.insert(individual.chromosome(), individual.fitness(), generation))
+            .count()
+    }
+
+    pub fn entries(&self) -> &[HallOfFameEntry] {
+        &self.entries
+    }
+    /// Лучшая особь за все время
+    pub fn best(&self) -> Option<&HallOfFameEntry> {
+        self.entries.first()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn insert(&mut self, chromosome: &Chromosome, fitness: f32, generation: usize) -> bool {
+        if fitness.is_nan() { return false; }
+        if self.entries.len() == self.capacity
+            && self.entries.last().is_some_and(|worst| worst.fitness >= fitness) {
+            return false;
+        }
+        let hash = genes_hash(chromosome);
+        if self.entries.iter().any(|e| e.hash == hash && same_genes(&e.chromosome, chromosome)) {
+            return false;
+        }
+        //после особей с тем же fitness, чтобы более ранние оставались выше
+        let position = self.entries.partition_point(|e| e.fitness >= fitness);
+        self.entries.insert(position, HallOfFameEntry {
+            chromosome: chromosome.clone(),
+            fitness,
+            generation,
+            hash,
+        });
+        self.entries.truncate(self.capacity);
+        true
+    }
+}
+
+//хэш генов с точными значениями весов (0.0 и -0.0 совпадают)
+fn genes_hash(chromosome: &Chromosome) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for (value, layer_num, neuron_out, neuron_in) in chromosome.iter() {
+        let bits = if value == 0.0 { 0 } else { value.to_bits() };
+        (bits, layer_num, neuron_out, neuron_in).hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+fn same_genes(a: &Chromosome, b: &Chromosome) -> bool {
+    a.len() == b.len() && a.iter().zip(b.iter()).all(|(x, y)| x == y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //fitness TestIndividual - сумма весов
+    fn individual(weights: &[f32]) -> TestIndividual {
+        TestIndividual::create(weights.iter().enumerate().map(|(n, w)| (*w, 2, n + 1, 0)).collect())
+    }
+
+    #[test]
+    fn keeps_best_distinct() {
+        let mut hall_of_fame = HallOfFame::new(3);
+
+        let added = hall_of_fame.update(&[
+            individual(&[1.0, 1.0]),
+            individual(&[1.0, 1.0]),//повтор
+            individual(&[0.5, 0.0]),
+        ]);
+        assert_eq!(added, 2);
+
+        let added = hall_of_fame.update(&[
+            individual(&[1.0, 1.0]),//уже есть с поколения 0
+            individual(&[2.0, 1.0]),
+            individual(&[0.0, 0.6]),
+            individual(&[0.1, 0.0]),//хуже всех при полном зале
+        ]);
+        assert_eq!(added, 2);
+
+        let entries: Vec<(f32, usize)> = hall_of_fame.entries()
+            .iter()
+            .map(|e| (e.fitness(), e.generation()))
+            .collect();
+        assert_eq!(entries, vec![(3.0, 1), (2.0, 0), (0.6, 1)]);
+        assert_eq!(hall_of_fame.best().unwrap().chromosome().len(), 2);
+
+        hall_of_fame.update_generation(10, &[individual(&[5.0, 0.0])]);
+        assert_eq!(hall_of_fame.best().unwrap().generation(), 10);
+        assert_eq!(hall_of_fame.len(), 3);
+        assert_eq!(HallOfFame::try_new(0).err(), Some(Error::InvalidParameter { name: "capacity", value: 0.0 }));
+    }
+}
@@ -5,6 +5,16 @@ pub trait Individual {
     fn chromosome(&self) -> &Chromosome;
     fn chromosome_mut(&mut self) -> &mut Chromosome;
     fn fitness(&self) -> f32;
+    /// Вектор целей для многокритериальной оптимизации (все цели максимизируются).
+    /// По умолчанию одна цель - fitness()
+    fn fitness_vector(&self) -> Vec<f32> {
+        vec![self.fitness()]
+    }
+}
+
+pub trait BehaviourIndividual: Individual {
+    //характеристика поведения особи, например конечная позиция или сводка пути птички
+    fn behaviour(&self) -> Vec<f32>;
 }
 
 pub trait IndividualFlex: Individual {
@@ -22,13 +32,21 @@ pub trait IndividualFlex: Individual {
 #[derive(Clone, Debug, PartialEq)]
 pub enum TestIndividual {
     WithChromosome { chromosome: Chromosome },
-    WithFitness { fitness: f32 },
+    WithFitness { fitness: f32, chromosome: Chromosome },
+    WithObjectives { objectives: Vec<f32>, chromosome: Chromosome },
 }
 
 #[cfg(test)]
 impl TestIndividual {
     pub fn new(fitness: f32) -> Self {
-        Self::WithFitness { fitness }
+        Self::WithFitness { fitness, chromosome: Chromosome::new(Vec::new()) }
+    }
+
+    pub fn with_objectives(objectives: &[f32]) -> Self {
+        Self::WithObjectives {
+            objectives: objectives.to_vec(),
+            chromosome: Chromosome::new(Vec::new()),
+        }
     }
 }
 
@@ -41,14 +59,16 @@ impl Individual for TestIndividual {
     fn chromosome(&self) -> &Chromosome {
         match self {
             Self::WithChromosome { chromosome } => chromosome,
-            Self::WithFitness { .. } => panic!("not supported for TestIndividual::WithFitness"),
+            Self::WithFitness { chromosome, .. } => chromosome,
+            Self::WithObjectives { chromosome, .. } => chromosome,
         }
     }
 
     fn chromosome_mut(&mut self) -> &mut Chromosome {
         match self {
             Self::WithChromosome { chromosome } => chromosome,
-            Self::WithFitness { .. } => panic!("not supported for TestIndividual::WithFitness"),
+            Self::WithFitness { chromosome, .. } => chromosome,
+            Self::WithObjectives { chromosome, .. } => chromosome,
         }
     }
 
@@ -57,7 +77,15 @@ impl Individual for TestIndividual {
             Self::WithChromosome { chromosome } =>
                 chromosome.iter()
                     .map(|(value, _, _, _)| value).sum(),
-            Self::WithFitness { fitness } => *fitness,
+            Self::WithFitness { fitness, .. } => *fitness,
+            Self::WithObjectives { objectives, .. } => objectives.iter().sum(),
+        }
+    }
+
+    fn fitness_vector(&self) -> Vec<f32> {
+        match self {
+            Self::WithObjectives { objectives, .. } => objectives.clone(),
+            _ => vec![self.fitness()],
         }
     }
     //
@@ -73,6 +101,14 @@ impl Individual for TestIndividual {
     // fn changed(&self) -> bool { true }
 }
 
+#[cfg(test)]
+impl BehaviourIndividual for TestIndividual {
+    //поведение - веса хромосомы
+    fn behaviour(&self) -> Vec<f32> {
+        self.chromosome().iter().map(|(value, _, _, _)| value).collect()
+    }
+}
+
 #[cfg(test)]
 #[derive(Clone, Debug, PartialEq)]
 pub struct FlexIndividual {
@@ -1,28 +1,50 @@
+mod error;
 mod individual;
 mod chromosome;
 mod crossover;
 mod mutation;
+mod operators;
 mod selection;
+mod scaling;
 mod statistics;
+mod statistics_history;
+mod hall_of_fame;
+mod genealogy;
 mod genetic_algorithm;
+mod genetic_algorithm_builder;
 mod genetic_flex_algorithm;
+mod differential_evolution;
+mod cma_es;
+mod novelty_search;
+mod map_elites;
+mod config;
 
+pub use self::error::*;
 pub use self::individual::*;
 pub use self::chromosome::*;
 pub use self::crossover::*;
 pub use self::mutation::*;
+pub use self::operators::*;
 pub use self::selection::*;
+pub use self::scaling::*;
 pub use self::statistics::*;
+pub use self::statistics_history::*;
+pub use self::hall_of_fame::*;
+pub use self::genealogy::*;
 pub use self::genetic_algorithm::*;
+pub use self::genetic_algorithm_builder::*;
 pub use self::genetic_flex_algorithm::*;
+pub use self::differential_evolution::*;
+pub use self::cma_es::*;
+pub use self::novelty_search::*;
+pub use self::map_elites::*;
+pub use self::config::*;
 use rand::seq::SliceRandom;
-use rand::{Rng, RngCore, seq::IteratorRandom};
-use std::collections::{HashMap, HashSet};
-use rand::thread_rng;
+use rand::{Rng, RngCore};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::cmp::Ordering;
 use std::iter::FromIterator;
 use std::ops::Index;
-use std::ops::Add;
 
 
 
@@ -82,6 +104,103 @@ mod tests {
         assert_eq!(population, expected_population);
     }
 
+    #[test]
+    fn test_child_pairs() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+        let ga = GeneticAlgorithm::new(
+            2500,
+            RouletteWheelSelection,
+            UniformCrossover,
+            GaussianMutation::new(0.0, 0.0),
+        ).with_child_pairs(true);
+
+        let population = vec![
+            individual(&[(1.0,2,1,0), (2.0,2,2,0), (1.0,2,3,0)]),
+            individual(&[(3.0,2,1,0), (4.0,2,2,0), (5.0,2,3,0)]),
+            individual(&[(3.0,2,1,0), (4.0,2,2,0), (5.0,2,3,0)]),
+        ];
+
+        //размер нечетной популяции сохраняется
+        let (new_population, statistic) = ga.evolve(&mut rng, &population);
+        assert_eq!(new_population.len(), 3);
+        assert_eq!(statistic.changed_count(), 3);
+        //без мутации первые два ребенка - дополняющая пара: их гены на каждой позиции
+        //это гены одной и той же пары родителей
+        let weights = |i: &TestIndividual| -> Vec<f32> {
+            i.chromosome().iter().map(|(w, ..)| w).collect()
+        };
+        let (a, b) = (weights(&new_population[0]), weights(&new_population[1]));
+        let parents: Vec<Vec<f32>> = population.iter().map(weights).collect();
+        assert!(parents.iter().any(|p| parents.iter().any(|q| {
+            (0..3).all(|j| (a[j], b[j]) == (p[j], q[j]) || (a[j], b[j]) == (q[j], p[j]))
+        })));
+    }
+
+    #[test]
+    fn test_child_pairs_genealogy() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+        //мутация для каждого ребенка выбирается отдельно: первая не меняет веса, вторая меняет все
+        let mutation: OneOf<dyn MutationMethod> = OneOf::new(vec![
+            Box::new(GaussianMutation::new(0.0, 0.0)),
+            Box::new(MutationChain::new(vec![Box::new(GaussianMutation::new(1.0, 0.5))])),
+        ], &[1.0, 1.0]);
+        let ga = GeneticAlgorithm::new(
+            2500,
+            RouletteWheelSelection,
+            UniformCrossover,
+            mutation,
+        ).with_child_pairs(true).with_genealogy(true);
+
+        let population: Vec<TestIndividual> = (0..6)
+            .map(|_| individual(&[(1.0,2,1,0), (1.0,2,2,0), (1.0,2,3,0)]))
+            .collect();
+        for _ in 0..5 {
+            let (new_population, _) = ga.evolve(&mut rng, &population);
+            let genealogy = ga.genealogy().unwrap();
+            for (child, id) in new_population.iter().zip(genealogy.ids()) {
+                let unchanged = child.chromosome().iter().all(|(w, ..)| w == 1.0);
+                let mutation = genealogy.record(*id).unwrap().mutation();
+                assert_eq!(mutation, Some(if unchanged { "GaussianMutation" } else { "MutationChain" }));
+            }
+        }
+    }
+
+    #[test]
+    fn test_steady_state() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+        let ga = GeneticAlgorithm::new(
+            2500,
+            RouletteWheelSelection,
+            UniformCrossover,
+            GaussianMutation::new(0.5, 0.5),
+        );
+
+        let population = vec![
+            individual(&[(1.0,0,0,0), (2.0,0,0,0), (1.0,0,0,0)]),
+            individual(&[(0.0,0,0,0), (0.0,0,0,0), (0.0,0,0,0)]),
+            individual(&[(1.0,0,0,0), (2.0,0,0,0), (4.0,0,0,0)]),
+            individual(&[(1.0,0,0,0), (1.0,0,0,0), (1.0,0,0,0)]),
+        ];
+
+        //заменяются 2 худшие особи, остальные не меняются
+        let (new_population, statistic) =
+            ga.evolve_steady_state(&mut rng, &population, 2, Replacement::Worst);
+        assert_eq!(statistic.changed_count(), 2);
+        assert_eq!(new_population[0], population[0]);
+        assert_ne!(new_population[1], population[1]);
+        assert_eq!(new_population[2], population[2]);
+        assert_ne!(new_population[3], population[3]);
+
+        //в обратном турнире из всей популяции всегда проигрывает худшая
+        let (new_population, _) = ga.evolve_steady_state(
+            &mut rng, &population, 1, Replacement::ReverseTournament { size: 16 });
+        assert_ne!(new_population[1], population[1]);
+        assert_eq!(&new_population[2..], &population[2..]);
+    }
+
     #[test]
     fn test_flex1() {
         let mut rng = ChaCha8Rng::from_seed(Default::default());
@@ -123,6 +242,200 @@ mod tests {
         ];
         population = ga.evolve(&mut rng, &population).0;
 
-        Some(population.len());
+        assert_eq!(population.len(), 3);
+    }
+
+    #[test]
+    fn test_flex1_dyn() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        //селекция и мутация выбираются во время работы
+        let selections: Vec<Box<dyn SelectionMethodDyn<FlexIndividual>>> = vec![
+            Box::new(RouletteWheelSelection),
+            Box::new(ScaledSelection::new(RouletteWheelSelection, RankScaling)),
+        ];
+        let genes = [(0.0,1,1,0), (1.0,1,1,1),  (0.0,1,2,0), (1.0,1,2,2),//1,2
+            (0.1,2,3,0), (0.2,2,3,1),(0.3,2,3,2),//3
+            (0.4,3,4,0), (0.5,3,4,3)];//4
+        let population = vec![
+            flex_individual(&genes, 5, 10.0f32, 1),//остается
+            flex_individual(&genes, 1, 9.0f32, 2),//под замену
+            flex_individual(&genes, 1, 8.0f32, 3),//под замену
+        ];
+        for selection_method in selections {
+            let mutation_method: Box<dyn MutationMethodFlexDyn<FlexIndividual>> =
+                Box::new(Flex1Mutation::new(0.5, 0.5, 9));
+            let ga = GeneticFlexAlgorithm::new(2500, selection_method, UniformCrossover, mutation_method)
+                .with_genealogy(true);
+            let (new_population, statistic) = ga.evolve(&mut rng, &population);
+
+            assert_eq!(new_population.len(), 3);
+            assert_eq!(statistic.changed_count(), 2);
+            //имя мутации берется у конкретного метода, а не у Box
+            let genealogy = ga.genealogy().unwrap();
+            assert_eq!(genealogy.record(genealogy.ids()[1]).unwrap().mutation(), Some("Flex1Mutation"));
+        }
+    }
+
+    #[test]
+    fn test_credit() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        //кроссовер, который запоминает переданную пригодность детей (None - очистка журнала)
+        struct CreditedCrossover(Rc<RefCell<Vec<Option<Vec<f32>>>>>);
+        impl CrossoverMethod for CreditedCrossover {
+            fn crossover(&self, rng: &mut dyn RngCore, parent_a: &Chromosome, parent_b: &Chromosome) -> Chromosome {
+                UniformCrossover.crossover(rng, parent_a, parent_b)
+            }
+            fn credit(&self, fitnesses: &[f32]) {
+                self.0.borrow_mut().push(Some(fitnesses.to_vec()));
+            }
+            fn reset_credit(&self) {
+                self.0.borrow_mut().push(None);
+            }
+        }
+
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let credited = Rc::new(RefCell::new(Vec::new()));
+        let mutation_method: Vec<Box<dyn MutationMethod>> = vec![
+            Box::new(GaussianMutation::new(0.5, 0.5)),
+            Box::new(MutationChain::new(vec![Box::new(GaussianMutation::new(0.5, 0.1))])),
+        ];
+        let ga = GeneticAlgorithm::new(
+            2500,
+            RouletteWheelSelection,
+            CreditedCrossover(credited.clone()),
+            AdaptiveOperators::new(mutation_method),
+        ).with_elitism(1);
+        let population = vec![
+            individual(&[(1.0, 2, 1, 0)]),
+            individual(&[(2.0, 2, 1, 0)]),
+            individual(&[(3.0, 2, 1, 0)]),
+        ];
+        let (mut population, _) = ga.evolve(&mut rng, &population);
+        assert_eq!(*credited.borrow(), vec![None]);//первое поколение, детей еще нет
+
+        //пригодность 2-х детей (без элиты) в порядке создания, даже если популяцию переставили
+        let fitnesses: Vec<f32> = population[1..].iter().map(|i| i.fitness()).collect();
+        population.reverse();
+        ga.evolve(&mut rng, &population);
+        assert_eq!(*credited.borrow(), vec![None, Some(fitnesses)]);
+
+        //чужая популяция: детей в ней нет, а после ошибки селекции журнал снова очищается
+        credited.borrow_mut().clear();
+        let population = vec![individual(&[(1.0, 2, 1, 0)]), individual(&[(f32::INFINITY, 2, 1, 0)])];
+        assert!(ga.try_evolve(&mut rng, &population).is_err());
+        assert_eq!(*credited.borrow(), vec![None, None]);
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+        assert!(matches!(GaussianMutation::try_new(1.5, 0.3),
+                         Err(Error::InvalidParameter { name: "chance", .. })));
+        assert!(Flex1Mutation::try_new(f32::NAN, 0.5, 9).is_err());
+        assert!(GeneticFlexAlgorithm::try_new(
+            100, RouletteWheelSelection, UniformCrossover, Flex1Mutation::new(0.5, 0.5, 9)).is_err());
+
+        let ga = GeneticAlgorithm::new(
+            2500,
+            RouletteWheelSelection,
+            UniformCrossover,
+            GaussianMutation::new(0.5, 0.5),
+        );
+        let empty: Vec<TestIndividual> = Vec::new();
+        assert_eq!(ga.try_evolve(&mut rng, &empty).err(), Some(Error::EmptyPopulation));
+        let population = vec![individual(&[(1.0, 2, 1, 0)]), individual(&[(2.0, 2, 1, 0)])];
+        assert!(matches!(
+            ga.try_evolve_steady_state(&mut rng, &population, 3, Replacement::Worst),
+            Err(Error::InvalidParameter { name: "children", .. })));
+
+        let ga = GeneticFlexAlgorithm::new(
+            2500,
+            RouletteWheelSelection,
+            UniformCrossover,
+            Flex1Mutation::new(0.5, 0.5, 9),
+        );
+        //слой 0 - хромосома без представления сети
+        let population = vec![flex_individual(&[(1.0, 0, 0, 0)], 1, 1.0, 2)];
+        assert_eq!(ga.try_evolve(&mut rng, &population).err(), Some(Error::MalformedChromosome { index: 0 }));
+        //сеть без скрытых слоев: мутация нейронов пропускается, а не паникует
+        let population: Vec<FlexIndividual> = [10.0, 1.0, 5.0].iter()
+            .map(|fitness| flex_individual(&[(0.0,1,1,0), (1.0,1,1,1), (0.1,2,2,0), (0.2,2,2,1)], 1, *fitness, 2))
+            .collect();
+        let (new_population, _) = ga.try_evolve(&mut rng, &population).unwrap();
+        assert_eq!(new_population.len(), 3);
+    }
+
+    #[test]
+    fn test_flex1_genealogy() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+        let ga =
+            GeneticFlexAlgorithm::new(
+            2500,
+            RouletteWheelSelection,
+            UniformCrossover,
+            Flex1Mutation::new(0.5, 0.5, 9),
+        ).with_genealogy(true);
+
+        let genes = [(0.0,1,1,0), (1.0,1,1,1),  (0.0,1,2,0), (1.0,1,2,2),//1,2
+            (0.1,2,3,0), (0.2,2,3,1),(0.3,2,3,2),//3
+            (0.4,3,4,0), (0.5,3,4,3)];//4
+        let population = vec![
+            flex_individual(&genes, 5, 10.0f32, 1),//остается
+            flex_individual(&genes, 1, 9.0f32, 2),//под замену
+            flex_individual(&genes, 1, 8.0f32, 3),//под замену
+        ];
+        let new_population = ga.evolve(&mut rng, &population).0;
+
+        let genealogy = ga.genealogy().unwrap();
+        let ids = genealogy.ids().to_vec();
+        assert_eq!(ids[0], 1);//оставшаяся особь сохраняет id
+        for (id, child) in ids.iter().zip(&new_population).skip(1) {
+            let record = genealogy.record(*id).unwrap();
+            assert_eq!(record.generation(), 1);
+            assert_eq!(record.parents().len(), 2);
+            assert_eq!(record.crossover(), Some("UniformCrossover"));
+            assert_eq!(record.mutation(), Some("Flex1Mutation"));
+            //структура меняется только вместе с записью об этом
+            let (_, neurons) = Chromosome::from_weights_to_flex_net_view(child.chromosome().iter());
+            let changed = neurons.iter().map(|layer| layer.len()).collect::<Vec<_>>() != vec![2, 1, 1];
+            assert_eq!(changed, !record.structural_changes().is_empty());
+        }
+        assert!(genealogy.records().iter().any(|r| !r.structural_changes().is_empty()));
+    }
+
+    #[test]
+    fn test_flex1_genealogy_survivors() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+        let ga = GeneticFlexAlgorithm::new(
+            2500,
+            RouletteWheelSelection,
+            UniformCrossover,
+            Flex1Mutation::new(1.0, 0.5, 9),
+        ).with_genealogy(true);
+
+        let genes = [(0.0,1,1,0), (1.0,1,1,1),  (0.0,1,2,0), (1.0,1,2,2),//1,2
+            (0.1,2,3,0), (0.2,2,3,1),(0.3,2,3,2),//3
+            (0.4,3,4,0), (0.5,3,4,3)];//4
+        let population = vec![
+            flex_individual(&genes, 5, 10.0f32, 1),//остается без мутации
+            flex_individual(&genes, 5, 6.0f32, 1),//остается, мутируют веса
+            flex_individual(&genes, 1, 1.0f32, 1),//под замену
+            flex_individual(&genes, 1, 0.0f32, 1),//под замену
+        ];
+        ga.evolve(&mut rng, &population);
+
+        let genealogy = ga.genealogy().unwrap();
+        let ids = genealogy.ids();
+        assert_eq!(ids[0], 1);
+        let record = genealogy.record(ids[1]).unwrap();
+        assert_eq!(record.parents(), &[2]);
+        assert_eq!(record.generation(), 1);
+        assert_eq!(record.crossover(), None);
+        assert_eq!(record.mutation(), Some("Flex1Mutation"));
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,252 @@
+use crate::*;
+use std::collections::BTreeMap;
+
+//MAP-Elites: архив-сетка по дескрипторам поведения (BehaviourIndividual::behaviour).
+//Каждое измерение дескриптора делится на bins ячеек в диапазоне range, в ячейке хранится
+//лучшая найденная особь (элита). Вместо одного чемпиона получается набор разных поведений.
+//На шаге evolve оцененная популяция вставляется в архив, а новая популяция создается
+//кроссовером и мутацией родителей, выбранных равновероятно из заполненных ячеек.
+pub struct MapElites {
+    ranges: Vec<(f32, f32)>,//диапазон значений каждого измерения дескриптора
+    bins: Vec<usize>,//кол. ячеек по каждому измерению
+    crossover_method: Box<dyn CrossoverMethod>,
+    mutation_method: Box<dyn MutationMethod>,
+    cells: BTreeMap<Vec<usize>, Elite>,//заполненные ячейки
+}
+
+/// Элита ячейки архива MAP-Elites
+#[derive(Clone, Debug)]
+pub struct Elite {
+    chromosome: Chromosome,
+    fitness: f32,
+    behaviour: Vec<f32>,
+}
+
+impl Elite {
+    pub fn chromosome(&self) -> &Chromosome { &self.chromosome }
+    pub fn fitness(&self) -> f32 { self.fitness }
+    pub fn behaviour(&self) -> &[f32] { &self.behaviour }
+}
+
+impl MapElites {
+    pub fn new(
+        ranges: &[(f32, f32)],
+        bins: &[usize],
+        crossover_method: impl CrossoverMethod + 'static,
+        mutation_method: impl MutationMethod + 'static,
+    ) -> Self {
+        Self::try_new(ranges, bins, crossover_method, mutation_method).unwrap_or_else(|err| panic!("{err}"))
+    }
+    /// new без паники: нужен хотя бы один диапазон min < max и по одному bins > 0 на каждый
+    pub fn try_new(
+        ranges: &[(f32, f32)],
+        bins: &[usize],
+        crossover_method: impl CrossoverMethod + 'static,
+        mutation_method: impl MutationMethod + 'static,
+    ) -> Result<Self, Error> {
+        if ranges.is_empty() {
+            return Err(Error::InvalidParameter { name: "ranges", value: 0.0 });
+        }
+        if ranges.len() != bins.len() {
+            return Err(Error::InvalidParameter { name: "bins", value: bins.len() as f32 });
+        }
+        if let Some((min, _)) = ranges.iter().find(|(min, max)| min.partial_cmp(max) != Some(Ordering::Less)) {
+            return Err(Error::InvalidParameter { name: "ranges", value: *min });
+        }
+        if bins.contains(&0) {
+            return Err(Error::InvalidParameter { name: "bins", value: 0.0 });
+        }
+
+        Ok(Self {
+            ranges: ranges.to_vec(),
+            bins: bins.to_vec(),
+            crossover_method: Box::new(crossover_method),
+            mutation_method: Box::new(mutation_method),
+            cells: BTreeMap::new(),
+        })
+    }
+    /// Ячейка сетки для дескриптора, значения вне диапазона попадают в крайние ячейки.
+    /// None, если длина дескриптора не совпадает с числом измерений сетки
+    pub fn cell(&self, behaviour: &[f32]) -> Option<Vec<usize>> {
+        if behaviour.len() != self.bins.len() {
+            return None;
+        }
+        let cell = behaviour.iter()
+            .zip(self.ranges.iter().zip(&self.bins))
+            .map(|(value, ((min, max), bins))| {
+                let position = (value - min) / (max - min) * *bins as f32;
+                (position.max(0.0) as usize).min(bins - 1)
+            })
+            .collect();
+        Some(cell)
+    }
+    pub fn elite(&self, cell: &[usize]) -> Option<&Elite> {
+        self.cells.get(cell)
+    }
+    /// Все элиты архива (по порядку ячеек)
+    pub fn elites(&self) -> impl Iterator<Item = (&Vec<usize>, &Elite)> {
+        self.cells.iter()
+    }
+    /// Вставка особи, если ее ячейка пуста или особь лучше элиты ячейки
+    pub fn insert<I>(&mut self, individual: &I) -> bool
+    where
+        I: BehaviourIndividual,
+    {
+        self.try_insert(individual).unwrap_or_else(|err| panic!("{err}"))
+    }
+    /// insert без паники: дескриптор неподходящей длины - ошибка InvalidBehaviour
+    pub fn try_insert<I>(&mut self, individual: &I) -> Result<bool, Error>
+    where
+        I: BehaviourIndividual,
+    {
+        let behaviour = individual.behaviour();
+        let fitness = individual.fitness();
+        let Some(cell) = self.cell(&behaviour) else {
+            return Err(Error::InvalidBehaviour { index: 0 });
+        };
+        if self.cells.get(&cell).is_some_and(|elite| elite.fitness >= fitness) {
+            return Ok(false);
+        }
+        self.cells.insert(cell, Elite {
+            chromosome: individual.chromosome().clone(),
+            fitness,
+            behaviour,
+        });
+        Ok(true)
+    }
+
+    pub fn evolve<I>(&mut self, rng: &mut dyn RngCore, population: &[I]) -> (Vec<I>, Statistics)
+    where
+        I: BehaviourIndividual,
+    {
+        self.try_evolve(rng, population).unwrap_or_else(|err| panic!("{err}"))
+    }
+    /// evolve без паники. Дескрипторы проверяются до вставки, при ошибке архив не меняется
+    pub fn try_evolve<I>(&mut self, rng: &mut dyn RngCore, population: &[I]) -> Result<(Vec<I>, Statistics), Error>
+    where
+        I: BehaviourIndividual,
+    {
+        if population.is_empty() {
+            return Err(Error::EmptyPopulation);
+        }
+        if let Some(index) = population.iter().position(|i| i.behaviour().len() != self.bins.len()) {
+            return Err(Error::InvalidBehaviour { index });
+        }
+
+        let mut ch_count = 0;
+        for individual in population {
+            if self.try_insert(individual)? {
+                ch_count += 1;
+            }
+        }
+        let elites: Vec<&Elite> = self.cells.values().collect();
+        let new_population = (0..population.len())
+            .map(|_| {
+                let parent_a = elites[rng.gen_range(0..elites.len())];
+                let parent_b = elites[rng.gen_range(0..elites.len())];
+
+                let mut child = self.crossover_method.crossover_with_fitness(
+                    rng, &parent_a.chromosome, parent_a.fitness, &parent_b.chromosome, parent_b.fitness);
+
+                self.mutation_method.mutate(rng, &mut child);
+
+                I::create(child)
+            })
+            .collect();
+        let mut statistic = Statistics::new(population);
+        statistic.set_changed_count(ch_count);//сколько особей улучшили свою ячейку
+        Ok((new_population, statistic))
+    }
+    /// Статистика архива: заполненность и QD-score
+    pub fn statistics(&self) -> MapElitesStatistics {
+        let cell_count = self.bins.iter().product();
+        let fitnesses = self.cells.values().map(|elite| elite.fitness);
+        MapElitesStatistics {
+            cell_count,
+            filled_count: self.cells.len(),
+            qd_score: fitnesses.clone().sum(),
+            max_fitness: fitnesses.reduce(f32::max),
+        }
+    }
+}
+
+/// Статистика архива MapElites
+#[derive(Clone, Debug, PartialEq)]
+pub struct MapElitesStatistics {
+    cell_count: usize,//всего ячеек в сетке
+    filled_count: usize,//заполненных ячеек
+    qd_score: f32,//сумма fitness всех элит
+    max_fitness: Option<f32>,//лучшая элита, None для пустого архива
+}
+
+impl MapElitesStatistics {
+    pub fn cell_count(&self) -> usize { self.cell_count }
+    pub fn filled_count(&self) -> usize { self.filled_count }
+    /// Доля заполненных ячеек сетки
+    pub fn coverage(&self) -> f32 { self.filled_count as f32 / self.cell_count as f32 }
+    /// QD-score: сумма fitness элит. Сравним между запусками, если fitness неотрицателен
+    pub fn qd_score(&self) -> f32 { self.qd_score }
+    pub fn max_fitness(&self) -> Option<f32> { self.max_fitness }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    //поведение TestIndividual - веса хромосомы, fitness - их сумма
+    fn individual(weights: &[f32]) -> TestIndividual {
+        TestIndividual::create(weights.iter().enumerate().map(|(n, w)| (*w, 2, n + 1, 0)).collect())
+    }
+
+    fn map_elites() -> MapElites {
+        MapElites::new(
+            &[(0.0, 1.0), (0.0, 1.0)],
+            &[2, 4],
+            UniformCrossover,
+            GaussianMutation::new(0.5, 0.1),
+        )
+    }
+
+    #[test]
+    fn insert() {
+        let mut archive = map_elites();
+
+        assert_eq!(archive.cell(&[0.1, 0.3]), Some(vec![0, 1]));
+        assert_eq!(archive.cell(&[-5.0, 5.0]), Some(vec![0, 3]));
+        assert_eq!(archive.cell(&[0.1]), None);
+        assert!(matches!(archive.try_insert(&individual(&[0.1])), Err(Error::InvalidBehaviour { .. })));
+
+        assert!(archive.insert(&individual(&[0.1, 0.3])));
+        assert!(!archive.insert(&individual(&[0.05, 0.3])));//хуже элиты ячейки
+        assert!(archive.insert(&individual(&[0.2, 0.4])));//лучше, заменяет
+        assert!(archive.insert(&individual(&[0.9, 0.9])));
+
+        assert_eq!(archive.elite(&[0, 1]).unwrap().behaviour(), &[0.2, 0.4]);
+        let statistics = archive.statistics();
+        assert_eq!(statistics.cell_count(), 8);
+        assert_eq!(statistics.filled_count(), 2);
+        approx::assert_relative_eq!(statistics.coverage(), 0.25);
+        approx::assert_relative_eq!(statistics.qd_score(), 0.6 + 1.8);
+        assert_eq!(statistics.max_fitness(), Some(1.8));
+    }
+
+    #[test]
+    fn coverage_grows() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut archive = map_elites();
+        let mut population: Vec<TestIndividual> = (0..10).map(|_| individual(&[0.5, 0.5])).collect();
+
+        let (_, first) = archive.evolve(&mut rng, &population);
+        assert_eq!(first.changed_count(), 1);
+        assert_eq!(archive.statistics().filled_count(), 1);
+
+        for _ in 0..50 {
+            population = archive.evolve(&mut rng, &population).0;
+        }
+        let statistics = archive.statistics();
+        assert!(statistics.filled_count() > 4);
+        assert!(statistics.qd_score() > first.max_fitness());
+    }
+}
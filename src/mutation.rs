@@ -1,18 +1,119 @@
 //Реализованные модули алгоритмов мутации
 mod gaussian;
 mod flex1;
+mod chain;
 //Экспорт алгоритмов
 pub use self::gaussian::*;
 pub use self::flex1::*;
+pub use self::chain::*;
 
 use crate::*;
+use std::fmt;
 
 pub trait MutationMethod {//мутация весом одной хромосомы без изменения структуры
     fn mutate(&self, rng: &mut dyn RngCore, child: &mut Chromosome);
+    //мутация, возвращающая имя примененного метода для родословной (Genealogy).
+    //Комбинаторы (OneOf, AdaptiveOperators) возвращают имя оператора, выбранного для ребенка
+    fn mutate_named(&self, rng: &mut dyn RngCore, child: &mut Chromosome) -> &'static str {
+        self.mutate(rng, child);
+        self.name()
+    }
+    //имя метода для родословной (Genealogy), по умолчанию - имя типа
+    fn name(&self) -> &'static str {
+        type_short_name::<Self>()
+    }
+    //пригодность детей, мутированных с прошлого вызова, в порядке мутации. Нужна адаптивным
+    //методам (AdaptiveOperators), вызывается в начале evolve. По умолчанию не используется
+    fn credit(&self, _fitnesses: &[f32]) {
+    }
+    //забыть детей, мутированных с прошлого вызова credit, без пригодности
+    fn reset_credit(&self) {
+    }
 }
 
 pub trait MutationMethodFlex {//мутация весов и структуры хромосом в популяции
     fn mutate<I>(&self, rng: &mut dyn RngCore, population: &mut [I])// -> Vec<I>
     where
         I: IndividualFlex;
-}
\ No newline at end of file
+    //мутация с отчетом об изменениях структуры каждой особи (по индексам популяции).
+    //По умолчанию изменения не сообщаются
+    fn mutate_traced<I>(&self, rng: &mut dyn RngCore, population: &mut [I]) -> Vec<Vec<StructuralChange>>
+    where
+        I: IndividualFlex,
+    {
+        self.mutate(rng, population);
+        vec![Vec::new(); population.len()]
+    }
+    //имя метода для родословной (Genealogy), по умолчанию - имя типа
+    fn name(&self) -> &'static str {
+        type_short_name::<Self>()
+    }
+}
+
+/// Объектно-безопасный вариант MutationMethodFlex для популяции особей I, чтобы мутацию
+/// можно было выбрать во время работы и хранить как Box<dyn MutationMethodFlexDyn<I>>.
+/// Реализован для любой MutationMethodFlex, методы с суффиксом _dyn
+pub trait MutationMethodFlexDyn<I> {
+    fn mutate_dyn(&self, rng: &mut dyn RngCore, population: &mut [I]);
+
+    fn mutate_traced_dyn(&self, rng: &mut dyn RngCore, population: &mut [I]) -> Vec<Vec<StructuralChange>> {
+        self.mutate_dyn(rng, population);
+        vec![Vec::new(); population.len()]
+    }
+
+    fn name_dyn(&self) -> &'static str {
+        type_short_name::<Self>()
+    }
+}
+
+impl<M, I> MutationMethodFlexDyn<I> for M
+where
+    M: MutationMethodFlex,
+    I: IndividualFlex,
+{
+    fn mutate_dyn(&self, rng: &mut dyn RngCore, population: &mut [I]) {
+        self.mutate(rng, population)
+    }
+
+    fn mutate_traced_dyn(&self, rng: &mut dyn RngCore, population: &mut [I]) -> Vec<Vec<StructuralChange>> {
+        self.mutate_traced(rng, population)
+    }
+
+    fn name_dyn(&self) -> &'static str {
+        self.name()
+    }
+}
+
+impl<I> MutationMethodFlexDyn<I> for Box<dyn MutationMethodFlexDyn<I>> {
+    fn mutate_dyn(&self, rng: &mut dyn RngCore, population: &mut [I]) {
+        self.as_ref().mutate_dyn(rng, population)
+    }
+
+    fn mutate_traced_dyn(&self, rng: &mut dyn RngCore, population: &mut [I]) -> Vec<Vec<StructuralChange>> {
+        self.as_ref().mutate_traced_dyn(rng, population)
+    }
+
+    fn name_dyn(&self) -> &'static str {
+        self.as_ref().name_dyn()
+    }
+}
+
+/// Изменение структуры сети при мутации (номера слоев с 1, как в генах)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StructuralChange {
+    AddNeuron { layer: usize, neuron: usize },
+    RemoveNeuron { layer: usize, neuron: usize },
+    AddLayer { layer: usize },//новый последний слой
+    RemoveLayer { layer: usize },
+}
+
+impl fmt::Display for StructuralChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AddNeuron { layer, neuron } => write!(f, "add_neuron({layer}:{neuron})"),
+            Self::RemoveNeuron { layer, neuron } => write!(f, "remove_neuron({layer}:{neuron})"),
+            Self::AddLayer { layer } => write!(f, "add_layer({layer})"),
+            Self::RemoveLayer { layer } => write!(f, "remove_layer({layer})"),
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use crate::*;
+//Цепочка мутаций: к ребенку по очереди применяются все методы, например
+//GaussianMutation с малым coeff для тонкой настройки и редкая с большим
+pub struct MutationChain {
+    methods: Vec<Box<dyn MutationMethod>>,
+}
+
+impl MutationChain {
+    pub fn new(methods: Vec<Box<dyn MutationMethod>>) -> Self {
+        Self { methods }
+    }
+}
+
+impl MutationMethod for MutationChain {
+    fn mutate(&self, rng: &mut dyn RngCore, child: &mut Chromosome) {
+        for method in &self.methods {
+            method.mutate(rng, child);
+        }
+    }
+    //каждый метод цепочки применялся ко всем детям
+    fn credit(&self, fitnesses: &[f32]) {
+        for method in &self.methods {
+            method.credit(fitnesses);
+        }
+    }
+
+    fn reset_credit(&self) {
+        for method in &self.methods {
+            method.reset_credit();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn chain() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let chain = MutationChain::new(vec![
+            Box::new(GaussianMutation::new(1.0, 0.1)),
+            Box::new(GaussianMutation::new(0.0, 10.0)),//не мутирует
+        ]);
+        let mut child: Chromosome = vec![(0.0, 2, 1, 0), (0.0, 2, 1, 1)].into_iter().collect();
+        chain.mutate(&mut rng, &mut child);
+
+        assert!(child.iter().all(|(w, _, _, _)| w != 0.0 && w.abs() <= 0.1));
+        assert_eq!(chain.name(), "MutationChain");
+    }
+}
@@ -12,13 +12,21 @@ impl Flex1Mutation {
                coeff: f32,
                eye_cells: usize,
     ) -> Self {
-        assert!((0.0..=1.0).contains(&chance));
+        Self::try_new(chance, coeff, eye_cells).unwrap_or_else(|err| panic!("{err}"))
+    }
 
-        Self {
+    pub fn try_new(chance: f32,
+                   coeff: f32,
+                   eye_cells: usize,
+    ) -> Result<Self, Error> {
+        if !(0.0..=1.0).contains(&chance) {
+            return Err(Error::InvalidParameter { name: "chance", value: chance });
+        }
+        Ok(Self {
             chance,
             coeff,
             eye_cells,//для мутации разного кол. входов
-        }
+        })
     }
 }
 
@@ -26,6 +34,14 @@ impl MutationMethodFlex for Flex1Mutation {
     fn mutate<I>(&self, rng: &mut dyn RngCore, population: &mut [I])// -> Vec<I>
     where
         I: IndividualFlex {
+        self.mutate_traced(rng, population);
+    }
+
+    fn mutate_traced<I>(&self, rng: &mut dyn RngCore, population: &mut [I]) -> Vec<Vec<StructuralChange>>
+    where
+        I: IndividualFlex {
+        //изменения структуры каждой особи, для родословной
+        let mut changes: Vec<Vec<StructuralChange>> = vec![Vec::new(); population.len()];
         // let mut new_population: Vec<I> = Vec::new();
         let mut max_n_out: usize = 0;//максимальный номер нейрона по популяции
         //Список слоев сетей популяции с максимальным номером нейрона, присвоенным этому слою.
@@ -55,14 +71,15 @@ impl MutationMethodFlex for Flex1Mutation {
             }
             //Мутация весов
             //(вес или bias, слой, нейрон, вх.связь или 0)
-            for (mut gene,layer_num,n_out,n_in)
-            in child.chromosome().iter() {
-                if layer_num > 1 && //веса и смещения первого слоя не мутируются!
-                    child.mut_force() > 0
+            let mut_force = child.mut_force();
+            for (gene,layer_num,_,_)
+            in child.chromosome_mut().iter_mut() {
+                if *layer_num > 1 && //веса и смещения первого слоя не мутируются!
+                    mut_force > 0
                 {
                     let sign = if rng.gen_bool(0.5) { -1.0 } else { 1.0 };
                     if rng.gen_bool(self.chance as _) {
-                        gene += sign * self.coeff * rng.gen::<f32>();//мутация
+                        *gene += sign * self.coeff * rng.gen::<f32>();//мутация
                     };
                 };
             };
@@ -76,31 +93,34 @@ impl MutationMethodFlex for Flex1Mutation {
         //обходим всю популяцию
         for child in &mut *population {
             if child.mut_force() != 2 { continue; };//только для силы мутации = 2
-            if child.changed() == false { continue; };//только заменяемые
+            if !child.changed() { continue; };//только заменяемые
             if !rng.gen_bool(chance_i as _) { continue; };//не будем ничего менять
             let eye_num: usize = rng.gen_range(1..=self.eye_cells * 2);//какой вход менять
             //(вес или bias, слой, нейрон, вх.связь или 0)
-            for (mut gene,layer_num,n_out,n_in)
-            in child.chromosome().iter() {
-                if layer_num != 1 { continue }//входы только на 1-м слое
-                if n_out != eye_num { continue }//только выбранный вход
+            for (gene,layer_num,n_out,n_in)
+            in child.chromosome_mut().iter_mut() {
+                if *layer_num != 1 { continue }//входы только на 1-м слое
+                //только связь выбранного входа, смещение входного нейрона не меняется
+                if *n_out != eye_num || *n_in != eye_num { continue }
 
                 if rng.gen_bool(0.5) {
-                    gene = 1.0;//добавление входа
+                    *gene = 1.0;//добавление входа
                 } else {
-                    gene = 0.0;//удаление входа
+                    *gene = 0.0;//удаление входа
                 };
             }
         }
         //добавление/удаление нейрона.----------------------------------------------
         //обходим всю популяцию
-        for child in &mut *population {
+        for (j, child) in population.iter_mut().enumerate() {
             if child.mut_force() != 2 { continue; };//только для силы мутации = 2
-            if child.changed() == false { continue; };//только заменяемые
+            if !child.changed() { continue; };//только заменяемые
             if !rng.gen_bool(chance_n as _) { continue; };//не будем ничего менять
             //для операции нужно представление сети
             let (mut inp_links, mut neurons) =
                 Chromosome::from_weights_to_flex_net_view(child.chromosome().iter());
+            //без скрытых слоев менять нечего: входной и выходной слой не меняются
+            if neurons.len() < 3 { continue; }
             //изменяемый номер слоя, не учитываем входной и выходной слой
             let l_num = rng.gen_range(2..neurons.len());
             // let l_num = layer_nums.iter().choose(rng).copied().unwrap();
@@ -121,6 +141,7 @@ impl MutationMethodFlex for Flex1Mutation {
                     //слоя и в следующий слой добавить связи к этому нейрону
                     layer_0.push(max_n);//добавляем нейрон в слой
                     layer_nums.insert((l_num,layer_0.len()), max_n);
+                    changes[j].push(StructuralChange::AddNeuron { layer: l_num, neuron: max_n });
                     //вх. связи этого нейрона
                     let num_0 = layer_0[0];//первый нейрон этого слоя, возьмем его связи
                     let links_0 = inp_links.get(&num_0).unwrap();
@@ -145,6 +166,7 @@ impl MutationMethodFlex for Flex1Mutation {
                     //нейронов
                 } else {  //просто удаляем нейрон
                     max_n = layer_0.pop().unwrap();//номер последнего нейрона
+                    changes[j].push(StructuralChange::RemoveNeuron { layer: l_num, neuron: max_n });
                     //вх. связи этого нейрона
                     inp_links.remove(&max_n);//удалим вх.связи удаленного нейрона
                     //вых. связи от этого нейрона
@@ -166,9 +188,9 @@ impl MutationMethodFlex for Flex1Mutation {
         };
         //добавление/удаление слоя.-------------------------------------------------
         //обходим всю популяцию
-        for child in &mut *population {
+        for (j, child) in population.iter_mut().enumerate() {
             if child.mut_force() != 3 { continue; }; //только для силы мутации = 3
-            if child.changed() == false { continue; };//только заменяемые
+            if !child.changed() { continue; };//только заменяемые
             if !rng.gen_bool(chance_l as _) { continue; }; //не будем ничего менять
             //для операции нужно представление сети
             let (mut inp_links, mut neurons) =
@@ -181,14 +203,14 @@ impl MutationMethodFlex for Flex1Mutation {
                 let mut layer_new: Vec<usize> = Vec::new();
                 //обходим нейроны последнего слоя и создаем новый слой по аналогии с ним
                 for (i, _) in layer_1.iter().enumerate() {
-                    let mut max_n: usize = 0;//следующий нейрон в слое
+                    //следующий нейрон в слое
                     //новый нейрон в новом слое уже забит в список макс. нейронов?
-                    if let Some(n) = layer_nums.get(&(l_num+1, i+1)) {
-                        max_n = *n;//возьмем тот номер, который есть
+                    let max_n: usize = if let Some(n) = layer_nums.get(&(l_num+1, i+1)) {
+                        *n//возьмем тот номер, который есть
                     } else {//новый нейрон для популяции
                         max_n_out += 1;
-                        max_n = max_n_out;
-                    }
+                        max_n_out
+                    };
                     //добавляем нейрон, при этом надо него добавить все входные связи этого
                     //слоя
                     layer_new.push(max_n);//добавляем нейрон в слой
@@ -204,6 +226,7 @@ impl MutationMethodFlex for Flex1Mutation {
                 }
                 //за целевым (последний) слоем добавляем новый последний слой
                 neurons.push(layer_new);
+                changes[j].push(StructuralChange::AddLayer { layer: neurons.len() });
             } else { //удаление слоя
                 //мы переносим нейроны из последующего слоя в удаляемый, при этом перестраивая
                 //связи каждого нейрона и заменяя его номер.
@@ -212,13 +235,13 @@ impl MutationMethodFlex for Flex1Mutation {
                 // let mut l_num = rng.gen_range(2..neurons.len());
                 let mut l_num = 2;
                 let mut layer_min = &neurons[1];
-                for j in 2..neurons.len() {//послойно
-                    let layer = &neurons[j];
+                for (k, layer) in neurons.iter().enumerate().skip(2) {//послойно
                     if layer_min.len() < layer.len() {
-                        l_num = j+1;//слой с мин. кол. нейронов
+                        l_num = k+1;//слой с мин. кол. нейронов
                         layer_min = layer;
                     }
                 }
+                changes[j].push(StructuralChange::RemoveLayer { layer: l_num });
                 //обход всех слоев начиная с удаляемого
                 for d in l_num-1 .. neurons.len() {
                     let layer_m = &neurons[d-1];//предыдущий слой
@@ -228,14 +251,13 @@ impl MutationMethodFlex for Flex1Mutation {
                         //копируем в него предыдущий, при этом пересобирая его вх. связи
                         for j in 0 .. layer_1.len() {
                             //определим добавляемый номер нейрона в слое layer_0 по количеству нейронов в нём
-                            let mut max_n: usize = 0;
                             //новый нейрон в слое уже забит в список макс. нейронов?
-                            if let Some(n) = layer_nums.get(&(d+1, j+1)) {
-                                max_n = *n;//возьмем тот номер, который есть
+                            let max_n: usize = if let Some(n) = layer_nums.get(&(d+1, j+1)) {
+                                *n//возьмем тот номер, который есть
                             } else {//новый нейрон для популяции
                                 max_n_out += 1;
-                                max_n = max_n_out;
-                            }
+                                max_n_out
+                            };
                             //добавляем нейрон, при этом надо него добавить все входные связи
                             //этого слоя, но не скопировать, а пересобрать заново
                             layer_0.push(max_n);//добавляем нейрон в слой
@@ -262,5 +284,41 @@ impl MutationMethodFlex for Flex1Mutation {
             //обновим хромосомы child
             child.chromosome_mut().update_genes(inp_links, neurons);
         }
+        changes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn mutates_weights_in_place() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let genes: Chromosome = vec![(0.0,1,1,0), (1.0,1,1,1),//1
+            (0.1,2,2,0), (0.2,2,2,1),//2
+            (0.3,3,3,0), (0.4,3,3,2)]//3
+            .into_iter()
+            .collect();
+        let mut population = vec![
+            <FlexIndividual as IndividualFlex>::create(genes.clone(), 1, false, 1),
+            <FlexIndividual as IndividualFlex>::create(genes.clone(), 1, false, 0),//без мутации
+        ];
+
+        let changes = Flex1Mutation::new(1.0, 0.5, 1).mutate_traced(&mut rng, &mut population);
+
+        assert!(changes.iter().all(|c| c.is_empty()));//сила 1 - только веса
+        let mutated = population[0].chromosome();
+        for ((w, l, o, i), (w0, ..)) in mutated.iter().zip(genes.iter()) {
+            //входы первого слоя не мутируются, остальные веса меняются не больше чем на coeff
+            if l == 1 {
+                assert_eq!(w, w0, "gene {:?}", (l, o, i));
+            } else {
+                assert!(w != w0 && (w - w0).abs() <= 0.5);
+            }
+        }
+        assert_eq!(population[1].chromosome().iter().collect::<Vec<_>>(), genes.iter().collect::<Vec<_>>());
     }
 }
@@ -8,9 +8,14 @@ pub struct GaussianMutation {
 
 impl GaussianMutation {
     pub fn new(chance: f32, coeff: f32) -> Self {
-        assert!((0.0..=1.0).contains(&chance));
+        Self::try_new(chance, coeff).unwrap_or_else(|err| panic!("{err}"))
+    }
 
-        Self { chance, coeff }
+    pub fn try_new(chance: f32, coeff: f32) -> Result<Self, Error> {
+        if !(0.0..=1.0).contains(&chance) {
+            return Err(Error::InvalidParameter { name: "chance", value: chance });
+        }
+        Ok(Self { chance, coeff })
     }
 }
 
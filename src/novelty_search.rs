@@ -0,0 +1,252 @@
+use crate::*;
+
+//Поиск новизны (novelty search): вместо fitness особи отбираются по новизне поведения -
+//среднему расстоянию до k ближайших соседей среди поведений популяции и архива.
+//Помогает на обманчивых ландшафтах fitness, где селекция по fitness застревает.
+//С fitness_weight > 0 отбор идет по смеси нормированных новизны и fitness:
+//(1 - fitness_weight) * novelty + fitness_weight * fitness.
+//Поведения, новизна которых больше archive_threshold, добавляются в архив.
+//Архив ограничен archive_capacity поведениями, при переполнении удаляются самые старые.
+pub struct NoveltySearch<S> {
+    selection_method: S,
+    crossover_method: Box<dyn CrossoverMethod>,
+    mutation_method: Box<dyn MutationMethod>,
+    k: usize,//число ближайших соседей
+    archive_threshold: f32,//порог новизны для добавления в архив
+    fitness_weight: f32,//вес fitness в смеси с новизной
+    archive_capacity: usize,//макс. размер архива
+    archive: Vec<Vec<f32>>,//архив новых поведений, от старых к новым
+}
+
+impl<S> NoveltySearch<S>
+where
+    S: FitnessSelectionMethod,
+{
+    pub fn new(
+        selection_method: S,
+        crossover_method: impl CrossoverMethod + 'static,
+        mutation_method: impl MutationMethod + 'static,
+        k: usize,
+        archive_threshold: f32,
+    ) -> Self {
+        Self::try_new(selection_method, crossover_method, mutation_method, k, archive_threshold)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+    /// new без паники: нужны k > 0 и archive_threshold >= 0
+    pub fn try_new(
+        selection_method: S,
+        crossover_method: impl CrossoverMethod + 'static,
+        mutation_method: impl MutationMethod + 'static,
+        k: usize,
+        archive_threshold: f32,
+    ) -> Result<Self, Error> {
+        if k == 0 {
+            return Err(Error::InvalidParameter { name: "k", value: 0.0 });
+        }
+        if !(0.0..).contains(&archive_threshold) {
+            return Err(Error::InvalidParameter { name: "archive_threshold", value: archive_threshold });
+        }
+
+        Ok(Self {
+            selection_method,
+            crossover_method: Box::new(crossover_method),
+            mutation_method: Box::new(mutation_method),
+            k,
+            archive_threshold,
+            fitness_weight: 0.0,
+            archive_capacity: 1000,
+            archive: Vec::new(),
+        })
+    }
+    /// Макс. размер архива (по умолчанию 1000), при переполнении удаляются самые старые
+    /// поведения. Новизна считается по всему архиву, поэтому он ограничивает и время evolve
+    pub fn with_archive_capacity(mut self, archive_capacity: usize) -> Self {
+        self.archive_capacity = archive_capacity;
+        self
+    }
+    /// Вес fitness в смеси с новизной: 0 - только новизна, 1 - только fitness.
+    /// Вес вне [0, 1] - ошибка try_evolve
+    pub fn with_fitness_weight(mut self, fitness_weight: f32) -> Self {
+        self.fitness_weight = fitness_weight;
+        self
+    }
+
+    pub fn archive(&self) -> &[Vec<f32>] {
+        &self.archive
+    }
+    /// Новизна каждого поведения: среднее расстояние до k ближайших соседей
+    /// среди остальных поведений и архива (поведения другой длины не учитываются)
+    pub fn novelty(&self, behaviours: &[Vec<f32>]) -> Vec<f32> {
+        behaviours.iter()
+            .enumerate()
+            .map(|(j, behaviour)| {
+                let mut distances: Vec<f32> = behaviours.iter()
+                    .enumerate()
+                    .filter(|(n, _)| *n != j)
+                    .map(|(_, other)| other)
+                    .chain(self.archive.iter())
+                    .filter_map(|other| euclidean_distance(behaviour, other))
+                    .collect();
+                if distances.is_empty() { return 0.0; }
+                distances.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+                let k = self.k.min(distances.len());
+                distances[..k].iter().sum::<f32>() / k as f32
+            })
+            .collect()
+    }
+
+    pub fn evolve<I>(&mut self, rng: &mut dyn RngCore, population: &[I]) -> (Vec<I>, Statistics)
+    where
+        I: BehaviourIndividual,
+    {
+        self.try_evolve(rng, population).unwrap_or_else(|err| panic!("{err}"))
+    }
+    /// evolve без паники: поведения всех особей должны быть одной длины
+    pub fn try_evolve<I>(&mut self, rng: &mut dyn RngCore, population: &[I]) -> Result<(Vec<I>, Statistics), Error>
+    where
+        I: BehaviourIndividual,
+    {
+        if population.is_empty() {
+            return Err(Error::EmptyPopulation);
+        }
+        if !(0.0..=1.0).contains(&self.fitness_weight) {
+            return Err(Error::InvalidParameter { name: "fitness_weight", value: self.fitness_weight });
+        }
+        let behaviours: Vec<Vec<f32>> = population.iter().map(|i| i.behaviour()).collect();
+        if let Some(index) = behaviours.iter().position(|b| b.len() != behaviours[0].len()) {
+            return Err(Error::InvalidBehaviour { index });
+        }
+
+        let novelty = self.novelty(&behaviours);
+        let fitnesses: Vec<f32> = population.iter().map(|i| i.fitness()).collect();
+        let novelty_n = normalize(&novelty);
+        let fitness_n = normalize(&fitnesses);
+        let scores: Vec<f32> = novelty_n.iter()
+            .zip(&fitness_n)
+            .map(|(n, f)| (1.0 - self.fitness_weight) * n + self.fitness_weight * f)
+            .collect();
+
+        let _generation = SelectionGeneration::begin(&self.selection_method, population);
+        let new_population = (0..population.len())
+            .map(|_| {
+                let parent = select_scaled(&self.selection_method, rng, population, &scores)?;
+                let parent_a = parent.0.chromosome();
+                let parent_b = parent.1.chromosome();
+
+                let mut child = self.crossover_method.crossover_with_fitness(
+                    rng, parent_a, parent.0.fitness(), parent_b, parent.1.fitness());
+
+                self.mutation_method.mutate(rng, &mut child);
+
+                Ok(I::create(child))
+            })
+            .collect::<Result<_, Error>>()?;
+
+        //архив пополняется после отбора, чтобы новизна поколения считалась по одному архиву
+        for (behaviour, value) in behaviours.iter().zip(&novelty) {
+            if *value > self.archive_threshold {
+                self.archive.push(behaviour.clone());
+            }
+        }
+        let excess = self.archive.len().saturating_sub(self.archive_capacity);
+        self.archive.drain(..excess);
+        let mut statistic = Statistics::new(population);
+        statistic.set_changed_count(population.len());
+        statistic.set_phenotype_diversity(&behaviours)?;
+        Ok((new_population, statistic))
+    }
+}
+
+//приведение значений к [0, 1], при нулевом диапазоне все значения 1
+fn normalize(values: &[f32]) -> Vec<f32> {
+    let min = values.iter().cloned().fold(f32::INFINITY, f32::min);
+    let max = values.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+    if max - min <= 0.0 {
+        return vec![1.0; values.len()];
+    }
+    values.iter().map(|v| (v - min) / (max - min)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn individual(weights: &[f32]) -> TestIndividual {
+        TestIndividual::create(weights.iter().enumerate().map(|(n, w)| (*w, 2, n + 1, 0)).collect())
+    }
+
+    #[test]
+    fn novelty_and_archive() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut search = NoveltySearch::new(
+            RouletteWheelSelection,
+            UniformCrossover,
+            GaussianMutation::new(0.5, 0.1),
+            2,
+            1.5,
+        );
+        let population = vec![
+            individual(&[0.0, 0.0]),
+            individual(&[0.0, 1.0]),
+            individual(&[1.0, 0.0]),
+            individual(&[5.0, 5.0]),//далеко от остальных
+        ];
+        let behaviours: Vec<Vec<f32>> = population.iter().map(|i| i.behaviour()).collect();
+
+        let novelty = search.novelty(&behaviours);
+        approx::assert_relative_eq!(novelty[0], 1.0);
+        assert!(novelty[3] > 6.0);
+
+        let (new_population, statistic) = search.evolve(&mut rng, &population);
+        assert_eq!(new_population.len(), population.len());
+        assert!(statistic.phenotype_diversity().is_some());
+        //в архив попало только далекое поведение
+        assert_eq!(search.archive(), &[vec![5.0, 5.0]]);
+
+        //архив учитывается: такое же поведение уже не так ново
+        let archived = search.novelty(&behaviours);
+        approx::assert_relative_eq!(archived[3], novelty[3] / 2.0);
+    }
+
+    #[test]
+    fn archive_capacity() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut search = NoveltySearch::new(
+            RouletteWheelSelection,
+            UniformCrossover,
+            GaussianMutation::new(0.5, 0.1),
+            1,
+            0.0,
+        ).with_archive_capacity(3);
+        let population: Vec<TestIndividual> = (0..4).map(|n| individual(&[n as f32, 0.0])).collect();
+
+        search.evolve(&mut rng, &population);
+        //все 4 поведения новые, самое старое (первое) не поместилось
+        assert_eq!(search.archive(), &[vec![1.0, 0.0], vec![2.0, 0.0], vec![3.0, 0.0]]);
+    }
+
+    #[test]
+    fn errors() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        assert!(NoveltySearch::try_new(
+            RouletteWheelSelection, UniformCrossover, GaussianMutation::new(0.5, 0.1), 0, 0.0).is_err());
+
+        let mut search = NoveltySearch::new(
+            RouletteWheelSelection,
+            UniformCrossover,
+            GaussianMutation::new(0.5, 0.1),
+            1,
+            0.0,
+        );
+        let population = vec![individual(&[0.0, 0.0]), individual(&[1.0])];
+        assert!(matches!(search.try_evolve(&mut rng, &population), Err(Error::InvalidBehaviour { index: 1 })));
+        assert!(search.archive().is_empty());
+
+        let mut search = search.with_fitness_weight(2.0);
+        let population = vec![individual(&[0.0, 0.0]), individual(&[1.0, 0.0])];
+        assert!(matches!(search.try_evolve(&mut rng, &population),
+            Err(Error::InvalidParameter { name: "fitness_weight", .. })));
+    }
+}
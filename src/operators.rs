@@ -0,0 +1,556 @@
+use crate::*;
+use std::cell::RefCell;
+//Комбинаторы операторов CrossoverMethod и MutationMethod, вместо одного кроссовера и одной
+//мутации в алгоритме:
+//- OneOf выбирает для каждого ребенка 1 оператор с вероятностью по весу;
+//- AdaptiveOperators подбирает вероятности операторов по улучшению fitness, которое дают
+//  их дети (adaptive pursuit). Пригодность детей алгоритм передает через credit в начале
+//  следующего evolve, а если детей в популяции нет - очищает журналы через reset_credit.
+//Оба запоминают, какой оператор создал каждого ребенка, и передают пригодность детей
+//вложенным операторам. crossover_named и mutate_named возвращают имя оператора, выбранного
+//для ребенка, поэтому в родословной (Genealogy) виден настоящий оператор.
+
+/// Выбор 1 оператора на ребенка с вероятностью, пропорциональной весу.
+/// OneOf<dyn CrossoverMethod> - кроссовер, OneOf<dyn MutationMethod> - мутация
+pub struct OneOf<T: ?Sized> {
+    operators: Operators<T>,
+    weights: Vec<f32>,
+}
+
+impl<T: ?Sized> OneOf<T> {
+    pub fn new(operators: Vec<Box<T>>, weights: &[f32]) -> Self {
+        Self::try_new(operators, weights).unwrap_or_else(|err| panic!("{err}"))
+    }
+    /// Ошибка, если операторов нет, их число не совпадает с числом весов, вес отрицательный
+    /// или все веса нулевые
+    pub fn try_new(operators: Vec<Box<T>>, weights: &[f32]) -> Result<Self, Error> {
+        if operators.is_empty() || operators.len() != weights.len() {
+            return Err(Error::InvalidParameter { name: "operators", value: operators.len() as f32 });
+        }
+        if let Some(weight) = weights.iter().find(|w| !w.is_finite() || **w < 0.0) {
+            return Err(Error::InvalidParameter { name: "weight", value: *weight });
+        }
+        if weights.iter().sum::<f32>() <= 0.0 {
+            return Err(Error::InvalidParameter { name: "weight", value: 0.0 });
+        }
+        Ok(Self {
+            operators: Operators::new(operators),
+            weights: weights.to_vec(),
+        })
+    }
+}
+
+/// Адаптивный выбор оператора (adaptive pursuit): у каждого оператора есть оценка q -
+/// скользящее среднее награды его детей, где награда - улучшение fitness ребенка над лучшим
+/// из родителей (для мутации - над средним fitness детей поколения), но не меньше 0.
+/// Вероятность оператора с лучшей оценкой стремится к p_max = 1 - (K - 1) * p_min,
+/// остальных - к p_min, так что ни один оператор не выключается совсем
+pub struct AdaptiveOperators<T: ?Sized> {
+    operators: Operators<T>,
+    p_min: f32,//минимальная вероятность оператора
+    alpha: f32,//скорость обновления оценок
+    beta: f32,//скорость обновления вероятностей
+    probabilities: RefCell<Vec<f32>>,
+    qualities: RefCell<Vec<f32>>,
+}
+
+impl<T: ?Sized> AdaptiveOperators<T> {
+    /// p_min = 0.2 / K, alpha = beta = 0.8
+    pub fn new(operators: Vec<Box<T>>) -> Self {
+        let p_min = 0.2 / operators.len().max(1) as f32;
+        Self::try_new(operators, p_min, 0.8, 0.8).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    pub fn try_new(operators: Vec<Box<T>>, p_min: f32, alpha: f32, beta: f32) -> Result<Self, Error> {
+        let count = operators.len();
+        if count == 0 {
+            return Err(Error::InvalidParameter { name: "operators", value: 0.0 });
+        }
+        if !(0.0..=1.0 / count as f32).contains(&p_min) {
+            return Err(Error::InvalidParameter { name: "p_min", value: p_min });
+        }
+        if !(alpha > 0.0 && alpha <= 1.0) {
+            return Err(Error::InvalidParameter { name: "alpha", value: alpha });
+        }
+        if !(beta > 0.0 && beta <= 1.0) {
+            return Err(Error::InvalidParameter { name: "beta", value: beta });
+        }
+        Ok(Self {
+            operators: Operators::new(operators),
+            p_min,
+            alpha,
+            beta,
+            probabilities: RefCell::new(vec![1.0 / count as f32; count]),
+            qualities: RefCell::new(vec![0.0; count]),
+        })
+    }
+    /// Текущие вероятности выбора операторов
+    pub fn probabilities(&self) -> Vec<f32> {
+        self.probabilities.borrow().clone()
+    }
+    /// Оценки операторов (скользящее среднее награды)
+    pub fn qualities(&self) -> Vec<f32> {
+        self.qualities.borrow().clone()
+    }
+
+    fn choose(&self, rng: &mut dyn RngCore) -> usize {
+        weighted_index(rng, &self.probabilities.borrow())
+    }
+    //обновление оценок операторов, которые создали детей, и затем вероятностей
+    fn update(&self, credited: &[(usize, Option<f32>, f32)]) {
+        if credited.is_empty() {
+            return;
+        }
+        let mean = credited.iter().map(|(_, _, fitness)| fitness).sum::<f32>() / credited.len() as f32;
+        let count = self.operators.list.len();
+        let mut rewards: Vec<(f32, usize)> = vec![(0.0, 0); count];
+        for (j, baseline, fitness) in credited {
+            let reward = (fitness - baseline.unwrap_or(mean)).max(0.0);
+            if reward.is_finite() {
+                rewards[*j].0 += reward;
+                rewards[*j].1 += 1;
+            }
+        }
+        let mut qualities = self.qualities.borrow_mut();
+        for (quality, (sum, n)) in qualities.iter_mut().zip(rewards) {
+            if n > 0 {
+                *quality += self.alpha * (sum / n as f32 - *quality);
+            }
+        }
+        //лучший оператор должен быть единственным, при равенстве вероятности не меняются
+        let top = qualities.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+        let mut tops = (0..count).filter(|j| qualities[*j] == top);
+        let (Some(best), None) = (tops.next(), tops.next()) else {
+            return;
+        };
+        let p_max = 1.0 - (count - 1) as f32 * self.p_min;
+        for (j, probability) in self.probabilities.borrow_mut().iter_mut().enumerate() {
+            let target = if j == best { p_max } else { self.p_min };
+            *probability += self.beta * (target - *probability);
+        }
+    }
+}
+
+impl CrossoverMethod for OneOf<dyn CrossoverMethod> {
+    fn crossover(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome,
+        parent_b: &Chromosome,
+    ) -> Chromosome {
+        let j = weighted_index(rng, &self.weights);
+        self.operators.apply(j, None, 1).crossover(rng, parent_a, parent_b)
+    }
+
+    fn crossover_with_fitness(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome,
+        fitness_a: f32,
+        parent_b: &Chromosome,
+        fitness_b: f32,
+    ) -> Chromosome {
+        self.crossover_named(rng, parent_a, fitness_a, parent_b, fitness_b).0
+    }
+
+    fn crossover_pair(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome,
+        parent_b: &Chromosome,
+    ) -> (Chromosome, Chromosome) {
+        let j = weighted_index(rng, &self.weights);
+        self.operators.apply(j, None, 2).crossover_pair(rng, parent_a, parent_b)
+    }
+
+    fn crossover_pair_with_fitness(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome,
+        fitness_a: f32,
+        parent_b: &Chromosome,
+        fitness_b: f32,
+    ) -> (Chromosome, Chromosome) {
+        self.crossover_pair_named(rng, parent_a, fitness_a, parent_b, fitness_b).0
+    }
+
+    fn crossover_named(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome,
+        fitness_a: f32,
+        parent_b: &Chromosome,
+        fitness_b: f32,
+    ) -> (Chromosome, &'static str) {
+        let j = weighted_index(rng, &self.weights);
+        self.operators.apply(j, Some(fitness_a.max(fitness_b)), 1)
+            .crossover_named(rng, parent_a, fitness_a, parent_b, fitness_b)
+    }
+
+    fn crossover_pair_named(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome,
+        fitness_a: f32,
+        parent_b: &Chromosome,
+        fitness_b: f32,
+    ) -> ((Chromosome, Chromosome), &'static str) {
+        let j = weighted_index(rng, &self.weights);
+        self.operators.apply(j, Some(fitness_a.max(fitness_b)), 2)
+            .crossover_pair_named(rng, parent_a, fitness_a, parent_b, fitness_b)
+    }
+
+    fn name(&self) -> &'static str {
+        "OneOf"
+    }
+
+    fn credit(&self, fitnesses: &[f32]) {
+        let credited = self.operators.take(fitnesses);
+        self.operators.forward(&credited, |operator, fitnesses| operator.credit(fitnesses));
+    }
+
+    fn reset_credit(&self) {
+        self.operators.reset(|operator| operator.reset_credit());
+    }
+}
+
+impl MutationMethod for OneOf<dyn MutationMethod> {
+    fn mutate(&self, rng: &mut dyn RngCore, child: &mut Chromosome) {
+        self.mutate_named(rng, child);
+    }
+
+    fn mutate_named(&self, rng: &mut dyn RngCore, child: &mut Chromosome) -> &'static str {
+        let j = weighted_index(rng, &self.weights);
+        self.operators.apply(j, None, 1).mutate_named(rng, child)
+    }
+
+    fn name(&self) -> &'static str {
+        "OneOf"
+    }
+
+    fn credit(&self, fitnesses: &[f32]) {
+        let credited = self.operators.take(fitnesses);
+        self.operators.forward(&credited, |operator, fitnesses| operator.credit(fitnesses));
+    }
+
+    fn reset_credit(&self) {
+        self.operators.reset(|operator| operator.reset_credit());
+    }
+}
+
+impl CrossoverMethod for AdaptiveOperators<dyn CrossoverMethod> {
+    fn crossover(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome,
+        parent_b: &Chromosome,
+    ) -> Chromosome {
+        let j = self.choose(rng);
+        self.operators.apply(j, None, 1).crossover(rng, parent_a, parent_b)
+    }
+
+    fn crossover_with_fitness(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome,
+        fitness_a: f32,
+        parent_b: &Chromosome,
+        fitness_b: f32,
+    ) -> Chromosome {
+        self.crossover_named(rng, parent_a, fitness_a, parent_b, fitness_b).0
+    }
+
+    fn crossover_pair(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome,
+        parent_b: &Chromosome,
+    ) -> (Chromosome, Chromosome) {
+        let j = self.choose(rng);
+        self.operators.apply(j, None, 2).crossover_pair(rng, parent_a, parent_b)
+    }
+
+    fn crossover_pair_with_fitness(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome,
+        fitness_a: f32,
+        parent_b: &Chromosome,
+        fitness_b: f32,
+    ) -> (Chromosome, Chromosome) {
+        self.crossover_pair_named(rng, parent_a, fitness_a, parent_b, fitness_b).0
+    }
+
+    fn crossover_named(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome,
+        fitness_a: f32,
+        parent_b: &Chromosome,
+        fitness_b: f32,
+    ) -> (Chromosome, &'static str) {
+        let j = self.choose(rng);
+        self.operators.apply(j, Some(fitness_a.max(fitness_b)), 1)
+            .crossover_named(rng, parent_a, fitness_a, parent_b, fitness_b)
+    }
+
+    fn crossover_pair_named(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome,
+        fitness_a: f32,
+        parent_b: &Chromosome,
+        fitness_b: f32,
+    ) -> ((Chromosome, Chromosome), &'static str) {
+        let j = self.choose(rng);
+        self.operators.apply(j, Some(fitness_a.max(fitness_b)), 2)
+            .crossover_pair_named(rng, parent_a, fitness_a, parent_b, fitness_b)
+    }
+
+    fn name(&self) -> &'static str {
+        "AdaptiveOperators"
+    }
+
+    fn credit(&self, fitnesses: &[f32]) {
+        let credited = self.operators.take(fitnesses);
+        self.update(&credited);
+        self.operators.forward(&credited, |operator, fitnesses| operator.credit(fitnesses));
+    }
+
+    fn reset_credit(&self) {
+        self.operators.reset(|operator| operator.reset_credit());
+    }
+}
+
+impl MutationMethod for AdaptiveOperators<dyn MutationMethod> {
+    fn mutate(&self, rng: &mut dyn RngCore, child: &mut Chromosome) {
+        self.mutate_named(rng, child);
+    }
+
+    fn mutate_named(&self, rng: &mut dyn RngCore, child: &mut Chromosome) -> &'static str {
+        let j = self.choose(rng);
+        self.operators.apply(j, None, 1).mutate_named(rng, child)
+    }
+
+    fn name(&self) -> &'static str {
+        "AdaptiveOperators"
+    }
+
+    fn credit(&self, fitnesses: &[f32]) {
+        let credited = self.operators.take(fitnesses);
+        self.update(&credited);
+        self.operators.forward(&credited, |operator, fitnesses| operator.credit(fitnesses));
+    }
+
+    fn reset_credit(&self) {
+        self.operators.reset(|operator| operator.reset_credit());
+    }
+}
+
+//Журналы операторов на время поколения алгоритма. В начале пригодность детей прошлого
+//поколения передается операторам через credit, если все дети найдены в популяции по генам
+//(популяцию можно переупорядочить), иначе журналы очищаются. Если поколение прервано
+//ошибкой (finish не вызван), журналы тоже очищаются, чтобы дети прерванного поколения
+//не получили пригодность чужих особей
+pub(crate) struct OperatorsGeneration<'a> {
+    crossover_method: &'a dyn CrossoverMethod,
+    mutation_method: Option<&'a dyn MutationMethod>,
+    finished: bool,
+}
+
+impl<'a> OperatorsGeneration<'a> {
+    //children - отпечатки генов детей прошлого поколения в порядке создания
+    pub(crate) fn begin<I>(
+        crossover_method: &'a dyn CrossoverMethod,
+        mutation_method: Option<&'a dyn MutationMethod>,
+        children: &[u64],
+        population: &[I],
+    ) -> Self
+    where
+        I: Individual,
+    {
+        let generation = Self { crossover_method, mutation_method, finished: false };
+        match children_fitnesses(children, population) {
+            Some(fitnesses) => {
+                crossover_method.credit(&fitnesses);
+                if let Some(mutation_method) = mutation_method {
+                    mutation_method.credit(&fitnesses);
+                }
+            }
+            None => generation.reset(),
+        }
+        generation
+    }
+    //поколение создано, журналы относятся к его детям
+    pub(crate) fn finish(mut self) {
+        self.finished = true;
+    }
+
+    fn reset(&self) {
+        self.crossover_method.reset_credit();
+        if let Some(mutation_method) = self.mutation_method {
+            mutation_method.reset_credit();
+        }
+    }
+}
+
+impl Drop for OperatorsGeneration<'_> {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.reset();
+        }
+    }
+}
+
+//пригодность детей по отпечаткам генов, None - если детей нет или кого-то нет в популяции
+fn children_fitnesses<I>(children: &[u64], population: &[I]) -> Option<Vec<f32>>
+where
+    I: Individual,
+{
+    if children.is_empty() {
+        return None;
+    }
+    let fitnesses: HashMap<u64, f32> = population.iter()
+        .map(|i| (i.chromosome().fingerprint(), i.fitness()))
+        .collect();
+    children.iter().map(|child| fitnesses.get(child).copied()).collect()
+}
+
+//Операторы и журнал их применения: какой оператор создал каждого ребенка
+struct Operators<T: ?Sized> {
+    list: Vec<Box<T>>,
+    applied: RefCell<Vec<(usize, Option<f32>)>>,//(оператор, лучший fitness родителей, если известен)
+}
+
+impl<T: ?Sized> Operators<T> {
+    fn new(list: Vec<Box<T>>) -> Self {
+        Self {
+            list,
+            applied: RefCell::new(Vec::new()),
+        }
+    }
+    //оператор j, который создает children детей
+    fn apply(&self, j: usize, baseline: Option<f32>, children: usize) -> &T {
+        self.applied.borrow_mut().extend(std::iter::repeat((j, baseline)).take(children));
+        &self.list[j]
+    }
+    //журнал применений вместе с пригодностью детей, журнал очищается.
+    //Лишние записи (например, отброшенный 2-й ребенок последней пары) не учитываются
+    fn take(&self, fitnesses: &[f32]) -> Vec<(usize, Option<f32>, f32)> {
+        self.applied.take()
+            .into_iter()
+            .zip(fitnesses)
+            .map(|((j, baseline), fitness)| (j, baseline, *fitness))
+            .collect()
+    }
+    //журнал очищается у самих комбинаторов и у вложенных
+    fn reset(&self, reset: impl Fn(&T)) {
+        self.applied.borrow_mut().clear();
+        self.list.iter().for_each(|operator| reset(operator));
+    }
+    //пригодность детей каждого оператора - ему самому (для вложенных комбинаторов)
+    fn forward(&self, credited: &[(usize, Option<f32>, f32)], credit: impl Fn(&T, &[f32])) {
+        for (j, operator) in self.list.iter().enumerate() {
+            let fitnesses: Vec<f32> = credited.iter()
+                .filter(|(k, _, _)| *k == j)
+                .map(|(_, _, fitness)| *fitness)
+                .collect();
+            if !fitnesses.is_empty() {
+                credit(operator, &fitnesses);
+            }
+        }
+    }
+}
+
+//случайный индекс с вероятностью, пропорциональной весу
+fn weighted_index(rng: &mut dyn RngCore, weights: &[f32]) -> usize {
+    let mut r = rng.gen::<f32>() * weights.iter().sum::<f32>();
+    for (j, weight) in weights.iter().enumerate() {
+        if r < *weight {
+            return j;
+        }
+        r -= weight;
+    }
+    //ошибка округления, берется последний оператор с ненулевым весом
+    weights.iter().rposition(|w| *w > 0.0).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn chromosome() -> Chromosome {
+        vec![(0.0, 2, 1, 0), (0.0, 2, 1, 1), (0.0, 2, 1, 2)].into_iter().collect()
+    }
+
+    #[test]
+    fn one_of() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let one_of: OneOf<dyn CrossoverMethod> = OneOf::new(
+            vec![Box::new(UniformCrossover), Box::new(ArithmeticCrossover::new(0.5))],
+            &[0.0, 1.0]);
+        for _ in 0..10 {
+            let (_, name) = one_of.crossover_named(&mut rng, &chromosome(), 0.0, &chromosome(), 0.0);
+            assert_eq!(name, "ArithmeticCrossover");
+        }
+        assert_eq!(CrossoverMethod::name(&one_of), "OneOf");//не зависит от прошлых вызовов
+
+        let operators: Vec<Box<dyn MutationMethod>> = vec![Box::new(GaussianMutation::new(0.5, 0.5))];
+        assert!(OneOf::try_new(operators, &[1.0, 1.0]).is_err());
+        let operators: Vec<Box<dyn MutationMethod>> = vec![Box::new(GaussianMutation::new(0.5, 0.5))];
+        assert_eq!(OneOf::try_new(operators, &[-1.0]).err(),
+                   Some(Error::InvalidParameter { name: "weight", value: -1.0 }));
+    }
+
+    #[test]
+    fn adaptive() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let adaptive: AdaptiveOperators<dyn MutationMethod> = AdaptiveOperators::new(vec![
+            Box::new(GaussianMutation::new(0.5, 0.5)),
+            Box::new(MutationChain::new(vec![Box::new(GaussianMutation::new(0.5, 0.5))])),
+        ]);
+        assert_eq!(adaptive.probabilities(), vec![0.5, 0.5]);
+
+        for _ in 0..5 {
+            //дети MutationChain лучше
+            let fitnesses: Vec<f32> = (0..20)
+                .map(|_| {
+                    let name = adaptive.mutate_named(&mut rng, &mut chromosome());
+                    if name == "MutationChain" { 1.0 } else { 0.0 }
+                })
+                .collect();
+            adaptive.credit(&fitnesses);
+        }
+        let probabilities = adaptive.probabilities();
+        assert!(probabilities[1] > 0.85 && probabilities[0] >= 0.1);
+        assert!(adaptive.qualities()[1] > adaptive.qualities()[0]);
+
+        let operators: Vec<Box<dyn CrossoverMethod>> = vec![Box::new(UniformCrossover)];
+        assert!(AdaptiveOperators::try_new(operators, 0.5, 0.8, 0.8).is_ok());
+        let operators: Vec<Box<dyn CrossoverMethod>> = vec![Box::new(UniformCrossover), Box::new(NeatCrossover)];
+        assert!(AdaptiveOperators::try_new(operators, 0.6, 0.8, 0.8).is_err());
+    }
+
+    #[test]
+    fn adaptive_tie() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let adaptive: AdaptiveOperators<dyn MutationMethod> = AdaptiveOperators::new(vec![
+            Box::new(GaussianMutation::new(0.5, 0.5)),
+            Box::new(MutationChain::new(vec![Box::new(GaussianMutation::new(0.5, 0.5))])),
+        ]);
+        //ни один оператор не получил награды - предпочитать некого
+        for _ in 0..5 {
+            let fitnesses: Vec<f32> = (0..20)
+                .map(|_| {
+                    adaptive.mutate_named(&mut rng, &mut chromosome());
+                    0.0
+                })
+                .collect();
+            adaptive.credit(&fitnesses);
+        }
+        assert_eq!(adaptive.qualities(), vec![0.0, 0.0]);
+        assert_eq!(adaptive.probabilities(), vec![0.5, 0.5]);
+    }
+}
@@ -0,0 +1,28 @@
+//Реализованные модули масштабирования пригодности
+mod linear;
+mod sigma;
+mod power;
+mod window;
+mod rank;
+//Экспорт алгоритмов
+pub use self::linear::*;
+pub use self::sigma::*;
+pub use self::power::*;
+pub use self::window::*;
+pub use self::rank::*;
+
+pub trait FitnessScaling {//преобразование fitness популяции перед селекцией
+    fn scale(&self, fitnesses: &[f32]) -> Vec<f32>;
+    //fitness очередного поколения, для масштабирования с учетом прошлых поколений
+    fn observe(&self, _fitnesses: &[f32]) {}
+}
+
+impl FitnessScaling for Box<dyn FitnessScaling> {
+    fn scale(&self, fitnesses: &[f32]) -> Vec<f32> {
+        self.as_ref().scale(fitnesses)
+    }
+
+    fn observe(&self, fitnesses: &[f32]) {
+        self.as_ref().observe(fitnesses)
+    }
+}
@@ -0,0 +1,74 @@
+use crate::*;
+//Линейное масштабирование f' = a*f + b (по Голдбергу): среднее сохраняется,
+//лучшая особь получает в c раз больше среднего. Если при этом худшие уходят в минус,
+//масштаб подбирается так, чтобы худшая особь получила 0.
+//Отрицательная пригодность предварительно сдвигается так, чтобы худшая особь имела 0.
+#[derive(Clone, Debug)]
+pub struct LinearScaling {
+    c: f32,//во сколько раз лучшая особь пригоднее средней, обычно 1.2..2.0
+}
+
+impl LinearScaling {
+    pub fn new(c: f32) -> Self {
+        Self::try_new(c).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    //c = 1 делит на 0 в scale, нужно c > 1
+    pub fn try_new(c: f32) -> Result<Self, Error> {
+        if !(c > 1.0 && c.is_finite()) {
+            return Err(Error::InvalidParameter { name: "c", value: c });
+        }
+        Ok(Self { c })
+    }
+}
+
+impl FitnessScaling for LinearScaling {
+    fn scale(&self, fitnesses: &[f32]) -> Vec<f32> {
+        let shift = min_max_avg(fitnesses).0.min(0.0);
+        let fitnesses: Vec<f32> = fitnesses.iter().map(|f| f - shift).collect();
+        let (min, max, avg) = min_max_avg(&fitnesses);
+        if max - avg <= f32::EPSILON {//все особи одинаковы
+            return vec![1.0; fitnesses.len()];
+        }
+        let (a, b) = if min > (self.c * avg - max) / (self.c - 1.0) {
+            let a = (self.c - 1.0) * avg / (max - avg);
+            (a, avg * (1.0 - a))
+        } else {
+            let a = avg / (avg - min);
+            (a, -min * a)
+        };
+        fitnesses.iter().map(|f| (a * f + b).max(0.0)).collect()
+    }
+}
+
+//min, max и среднее fitness популяции
+pub(crate) fn min_max_avg(fitnesses: &[f32]) -> (f32, f32, f32) {
+    let min = fitnesses.iter().copied().fold(f32::INFINITY, f32::min);
+    let max = fitnesses.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    let avg = fitnesses.iter().sum::<f32>() / fitnesses.len() as f32;
+    (min, max, avg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test() {
+        let scaled = LinearScaling::new(2.0).scale(&[1.0, 2.0, 3.0]);
+
+        approx::assert_relative_eq!(scaled.as_slice(), [0.0, 2.0, 4.0].as_slice());
+
+        //худшие не уходят в минус
+        let scaled = LinearScaling::new(2.0).scale(&[0.0, 10.0, 10.0, 10.0]);
+        approx::assert_relative_eq!(scaled.as_slice(), [0.0, 10.0, 10.0, 10.0].as_slice());
+
+        //отрицательная пригодность
+        let scaled = LinearScaling::new(1.5).scale(&[-10.0, -6.0, -5.0]);
+        approx::assert_relative_eq!(scaled.as_slice(), [0.75, 3.75, 4.5].as_slice());
+
+        assert!(LinearScaling::try_new(1.0).is_err());
+        assert!(LinearScaling::try_new(f32::INFINITY).is_err());
+        assert!(LinearScaling::try_new(f32::NAN).is_err());
+    }
+}
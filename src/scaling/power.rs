@@ -0,0 +1,38 @@
+use crate::*;
+//Степенное масштабирование f' = f^k. При k > 1 давление селекции растет,
+//при k < 1 - падает. Отрицательная пригодность считается нулевой.
+#[derive(Clone, Debug)]
+pub struct PowerLawScaling {
+    k: f32,//показатель степени
+}
+
+impl PowerLawScaling {
+    pub fn new(k: f32) -> Self {
+        Self::try_new(k).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    pub fn try_new(k: f32) -> Result<Self, Error> {
+        if !(k > 0.0 && k.is_finite()) {
+            return Err(Error::InvalidParameter { name: "k", value: k });
+        }
+        Ok(Self { k })
+    }
+}
+
+impl FitnessScaling for PowerLawScaling {
+    fn scale(&self, fitnesses: &[f32]) -> Vec<f32> {
+        fitnesses.iter().map(|f| f.max(0.0).powf(self.k)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test() {
+        let scaled = PowerLawScaling::new(2.0).scale(&[-1.0, 1.0, 3.0]);
+
+        approx::assert_relative_eq!(scaled.as_slice(), [0.0, 1.0, 9.0].as_slice());
+    }
+}
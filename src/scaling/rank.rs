@@ -0,0 +1,41 @@
+use crate::*;
+//Ранговая нормализация: f' = ранг особи / кол. особей (худшая 1/n, лучшая 1).
+//Равные fitness получают средний ранг. Давление селекции не зависит
+//ни от знака, ни от масштаба fitness.
+#[derive(Clone, Debug, Default)]
+pub struct RankScaling;
+
+impl FitnessScaling for RankScaling {
+    fn scale(&self, fitnesses: &[f32]) -> Vec<f32> {
+        let len = fitnesses.len();
+        let mut order: Vec<usize> = (0..len).collect();
+        order.sort_by(|a, b| fitnesses[*a].partial_cmp(&fitnesses[*b]).unwrap_or(Ordering::Equal));
+        let mut scaled = vec![0.0; len];
+        let mut j = 0;
+        while j < len {
+            //группа равных fitness
+            let mut k = j;
+            while k + 1 < len && fitnesses[order[k + 1]] == fitnesses[order[j]] {
+                k += 1;
+            }
+            let rank = (j + k) as f32 / 2.0 + 1.0;
+            for index in &order[j..=k] {
+                scaled[*index] = rank / len as f32;
+            }
+            j = k + 1;
+        }
+        scaled
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test() {
+        let scaled = RankScaling.scale(&[-5.0, 100.0, 3.0, 3.0]);
+
+        approx::assert_relative_eq!(scaled.as_slice(), [0.25, 1.0, 0.625, 0.625].as_slice());
+    }
+}
@@ -0,0 +1,45 @@
+use crate::*;
+//Сигма-отсечение: f' = max(0, f - (avg - c*sigma)).
+//Особи хуже среднего более чем на c стандартных отклонений получают 0,
+//давление селекции не зависит от абсолютного смещения fitness.
+#[derive(Clone, Debug)]
+pub struct SigmaTruncation {
+    c: f32,//кол. стандартных отклонений, обычно 1..3
+}
+
+impl SigmaTruncation {
+    pub fn new(c: f32) -> Self {
+        Self::try_new(c).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    pub fn try_new(c: f32) -> Result<Self, Error> {
+        if !(0.0..).contains(&c) {
+            return Err(Error::InvalidParameter { name: "c", value: c });
+        }
+        Ok(Self { c })
+    }
+}
+
+impl FitnessScaling for SigmaTruncation {
+    fn scale(&self, fitnesses: &[f32]) -> Vec<f32> {
+        let (_, _, avg) = min_max_avg(fitnesses);
+        let sigma = (fitnesses.iter()
+            .map(|f| (f - avg).powi(2))
+            .sum::<f32>() / fitnesses.len() as f32)
+            .sqrt();
+        fitnesses.iter().map(|f| (f - (avg - self.c * sigma)).max(0.0)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test() {
+        //avg = 1000, sigma = 1
+        let scaled = SigmaTruncation::new(1.0).scale(&[999.0, 1001.0]);
+
+        approx::assert_relative_eq!(scaled.as_slice(), [0.0, 2.0].as_slice());
+    }
+}
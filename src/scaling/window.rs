@@ -0,0 +1,60 @@
+use crate::*;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+//Оконное масштабирование: f' = f - (худший fitness за последние window поколений).
+//Убирает большое абсолютное смещение fitness, сохраняя давление селекции.
+//Поколения учитываются через observe (SelectionMethod::begin_generation у ScaledSelection).
+#[derive(Clone, Debug)]
+pub struct WindowScaling {
+    window: usize,//кол. поколений K
+    worst: RefCell<VecDeque<f32>>,//худший fitness последних поколений
+}
+
+impl WindowScaling {
+    pub fn new(window: usize) -> Self {
+        Self::try_new(window).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    pub fn try_new(window: usize) -> Result<Self, Error> {
+        if window == 0 {
+            return Err(Error::InvalidParameter { name: "window", value: 0.0 });
+        }
+        Ok(Self {
+            window,
+            worst: RefCell::new(VecDeque::with_capacity(window)),
+        })
+    }
+}
+
+impl FitnessScaling for WindowScaling {
+    fn scale(&self, fitnesses: &[f32]) -> Vec<f32> {
+        let (min, _, _) = min_max_avg(fitnesses);
+        let worst = self.worst.borrow().iter().copied().fold(min, f32::min);
+        fitnesses.iter().map(|f| f - worst).collect()
+    }
+
+    fn observe(&self, fitnesses: &[f32]) {
+        let mut worst = self.worst.borrow_mut();
+        if worst.len() == self.window {
+            worst.pop_front();
+        }
+        worst.push_back(min_max_avg(fitnesses).0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test() {
+        let scaling = WindowScaling::new(2);
+
+        scaling.observe(&[100.0, 110.0]);
+        approx::assert_relative_eq!(scaling.scale(&[105.0, 110.0]).as_slice(), [5.0, 10.0].as_slice());
+
+        scaling.observe(&[105.0, 110.0]);
+        scaling.observe(&[107.0, 110.0]);//поколение со 100 вышло из окна
+        approx::assert_relative_eq!(scaling.scale(&[107.0, 110.0]).as_slice(), [2.0, 5.0].as_slice());
+    }
+}
@@ -1,12 +1,193 @@
 //Реализованные модули алгоритмов селекции
 mod roulette_wheel;
+mod nsga2;
+mod scaled;
 //Экспорт алгоритмов
 pub use self::roulette_wheel::*;
+pub use self::nsga2::*;
+pub use self::scaled::*;
 
 use crate::*;
+use std::marker::PhantomData;
 
 pub trait SelectionMethod {
     fn select<'a, I>(&self, rng: &mut dyn RngCore, population: &'a [I]) -> (&'a I, &'a I)
     where
         I: Individual;
-}
\ No newline at end of file
+    //выбор родителей без паники
+    fn try_select<'a, I>(&self, rng: &mut dyn RngCore, population: &'a [I]) -> Result<(&'a I, &'a I), Error>
+    where
+        I: Individual,
+    {
+        if population.is_empty() {
+            return Err(Error::EmptyPopulation);
+        }
+        Ok(self.select(rng, population))
+    }
+    //индексы 2-х родителей в популяции (нужны родословной)
+    fn try_select_indices<I>(&self, rng: &mut dyn RngCore, population: &[I]) -> Result<(usize, usize), Error>
+    where
+        I: Individual,
+    {
+        let (a, b) = self.try_select(rng, population)?;
+        let index = |parent: &I| population.iter()
+            .position(|i| std::ptr::eq(i, parent))
+            .expect("selected parent is not from the population");
+        Ok((index(a), index(b)))
+    }
+    //подготовка к селекции из нового поколения, вызывается в начале evolve
+    fn begin_generation<I>(&self, _population: &[I])
+    where
+        I: Individual,
+    {
+    }
+    //конец поколения: данные, подготовленные в begin_generation, больше не действительны.
+    //Вызывается при выходе из evolve, в том числе по ошибке
+    fn end_generation(&self) {
+    }
+}
+
+/// Селекция по отдельно заданному fitness (например, масштабированному в ScaledSelection
+/// или по новизне в NoveltySearch) вместо fitness() особей: fitnesses[j] - пригодность population[j]
+pub trait FitnessSelectionMethod: SelectionMethod {
+    //индексы 2-х родителей
+    fn try_select_by_fitness<I>(
+        &self,
+        rng: &mut dyn RngCore,
+        population: &[I],
+        fitnesses: &[f32],
+    ) -> Result<(usize, usize), Error>
+    where
+        I: Individual;
+}
+
+/// Объектно-безопасный вариант SelectionMethod для популяции особей I: селекцию можно выбрать
+/// во время работы (например, из AlgorithmConfig) и хранить как Box<dyn SelectionMethodDyn<I>>.
+/// Реализован для любой SelectionMethod. Методы названы с суффиксом _dyn, чтобы вызовы
+/// у конкретных селекций не были неоднозначными
+pub trait SelectionMethodDyn<I> {
+    fn select_dyn<'a>(&self, rng: &mut dyn RngCore, population: &'a [I]) -> (&'a I, &'a I);
+
+    fn try_select_dyn<'a>(&self, rng: &mut dyn RngCore, population: &'a [I]) -> Result<(&'a I, &'a I), Error> {
+        if population.is_empty() {
+            return Err(Error::EmptyPopulation);
+        }
+        Ok(self.select_dyn(rng, population))
+    }
+    //индексы родителей в популяции (нужны родословной)
+    fn try_select_indices_dyn(&self, rng: &mut dyn RngCore, population: &[I]) -> Result<(usize, usize), Error>;
+
+    fn begin_generation_dyn(&self, _population: &[I]) {
+    }
+
+    fn end_generation_dyn(&self) {
+    }
+}
+
+impl<S, I> SelectionMethodDyn<I> for S
+where
+    S: SelectionMethod,
+    I: Individual,
+{
+    fn select_dyn<'a>(&self, rng: &mut dyn RngCore, population: &'a [I]) -> (&'a I, &'a I) {
+        self.select(rng, population)
+    }
+
+    fn try_select_dyn<'a>(&self, rng: &mut dyn RngCore, population: &'a [I]) -> Result<(&'a I, &'a I), Error> {
+        self.try_select(rng, population)
+    }
+
+    fn try_select_indices_dyn(&self, rng: &mut dyn RngCore, population: &[I]) -> Result<(usize, usize), Error> {
+        self.try_select_indices(rng, population)
+    }
+
+    fn begin_generation_dyn(&self, population: &[I]) {
+        self.begin_generation(population)
+    }
+
+    fn end_generation_dyn(&self) {
+        self.end_generation()
+    }
+}
+
+impl<I> SelectionMethodDyn<I> for Box<dyn SelectionMethodDyn<I>> {
+    fn select_dyn<'a>(&self, rng: &mut dyn RngCore, population: &'a [I]) -> (&'a I, &'a I) {
+        self.as_ref().select_dyn(rng, population)
+    }
+
+    fn try_select_dyn<'a>(&self, rng: &mut dyn RngCore, population: &'a [I]) -> Result<(&'a I, &'a I), Error> {
+        self.as_ref().try_select_dyn(rng, population)
+    }
+
+    fn try_select_indices_dyn(&self, rng: &mut dyn RngCore, population: &[I]) -> Result<(usize, usize), Error> {
+        self.as_ref().try_select_indices_dyn(rng, population)
+    }
+
+    fn begin_generation_dyn(&self, population: &[I]) {
+        self.as_ref().begin_generation_dyn(population)
+    }
+
+    fn end_generation_dyn(&self) {
+        self.as_ref().end_generation_dyn()
+    }
+}
+
+//Поколение селекции внутри evolve: begin_generation при создании, end_generation при
+//выходе из evolve (drop), в том числе по ошибке через ?
+pub(crate) struct SelectionGeneration<'s, S, I>
+where
+    S: SelectionMethodDyn<I> + ?Sized,
+{
+    selection_method: &'s S,
+    population: PhantomData<fn(&I)>,
+}
+
+impl<'s, S, I> SelectionGeneration<'s, S, I>
+where
+    S: SelectionMethodDyn<I> + ?Sized,
+{
+    pub(crate) fn begin(selection_method: &'s S, population: &[I]) -> Self {
+        selection_method.begin_generation_dyn(population);
+        Self { selection_method, population: PhantomData }
+    }
+}
+
+impl<S, I> Drop for SelectionGeneration<'_, S, I>
+where
+    S: SelectionMethodDyn<I> + ?Sized,
+{
+    fn drop(&mut self) {
+        self.selection_method.end_generation_dyn();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    //сторонняя селекция, которая реализует только select
+    struct LastTwo;
+
+    impl SelectionMethod for LastTwo {
+        fn select<'a, I>(&self, _rng: &mut dyn RngCore, population: &'a [I]) -> (&'a I, &'a I)
+        where
+            I: Individual,
+        {
+            (&population[population.len() - 1], &population[population.len() / 2])
+        }
+    }
+
+    #[test]
+    fn select_only() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let population = vec![TestIndividual::new(1.0), TestIndividual::new(2.0), TestIndividual::new(3.0)];
+        let empty: Vec<TestIndividual> = Vec::new();
+
+        assert_eq!(LastTwo.try_select_indices(&mut rng, &population), Ok((2, 1)));
+        assert_eq!(LastTwo.try_select(&mut rng, &empty), Err(Error::EmptyPopulation));
+        let selection: Box<dyn SelectionMethodDyn<TestIndividual>> = Box::new(LastTwo);
+        assert_eq!(selection.try_select_indices_dyn(&mut rng, &population), Ok((2, 1)));
+    }
+}
@@ -0,0 +1,297 @@
+use crate::*;
+use std::cell::RefCell;
+//NSGA-II для многокритериальной оптимизации (Individual::fitness_vector).
+//Популяция разбивается на фронты быстрой недоминируемой сортировкой, внутри фронта особи
+//сравниваются по расстоянию скученности (crowding distance). Селекция выбирает родителей
+//бинарным турниром: побеждает особь с меньшим номером фронта, а при равенстве - с большим
+//расстоянием. Ранги считаются один раз на поколение в begin_generation.
+//Вторая половина NSGA-II - отбор в окружении (environmental_selection): родители и
+//оцененные потомки объединяются и обрезаются до размера популяции по фронтам и скученности.
+#[derive(Clone, Debug, Default)]
+pub struct Nsga2Selection {
+    ranks: RefCell<Option<Vec<(usize, f32)>>>,//ранги текущего поколения
+}
+
+impl Nsga2Selection {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Ранги особей популяции: (номер фронта с 0, расстояние скученности)
+    pub fn ranks<I>(population: &[I]) -> Vec<(usize, f32)>
+    where
+        I: Individual,
+    {
+        Self::try_ranks(population).unwrap_or_else(|err| panic!("{err}"))
+    }
+    /// ranks без паники: fitness_vector всех особей должны быть одной длины
+    pub fn try_ranks<I>(population: &[I]) -> Result<Vec<(usize, f32)>, Error>
+    where
+        I: Individual,
+    {
+        let objectives = objectives(population)?;
+        let mut ranks = vec![(0, 0.0); population.len()];
+        for (front_num, front) in non_dominated_sort(&objectives).iter().enumerate() {
+            for (j, distance) in crowding_distance(&objectives, front).into_iter().enumerate() {
+                ranks[front[j]] = (front_num, distance);
+            }
+        }
+        Ok(ranks)
+    }
+    /// Отбор в окружении: индексы size особей, переходящих в следующее поколение.
+    /// Фронты берутся целиком по порядку, последний неполный - по убыванию скученности
+    pub fn survivors<I>(population: &[I], size: usize) -> Vec<usize>
+    where
+        I: Individual,
+    {
+        let objectives = objectives(population).unwrap_or_else(|err| panic!("{err}"));
+        let mut survivors: Vec<usize> = Vec::with_capacity(size.min(population.len()));
+        for front in non_dominated_sort(&objectives) {
+            if survivors.len() + front.len() <= size {
+                survivors.extend(front);
+                continue;
+            }
+            let distance = crowding_distance(&objectives, &front);
+            let mut order: Vec<usize> = (0..front.len()).collect();
+            order.sort_by(|a, b| distance[*b].partial_cmp(&distance[*a]).unwrap_or(Ordering::Equal));
+            survivors.extend(order.iter().take(size - survivors.len()).map(|j| front[*j]));
+            break;
+        }
+        survivors
+    }
+    /// Отбор в окружении NSGA-II: population - родители вместе с оцененными потомками,
+    /// остается size особей (см. survivors). Результат передается в evolve
+    pub fn environmental_selection<I>(population: Vec<I>, size: usize) -> Vec<I>
+    where
+        I: Individual,
+    {
+        let survivors = Self::survivors(&population, size);
+        let mut population: Vec<Option<I>> = population.into_iter().map(Some).collect();
+        survivors.iter().filter_map(|j| population[*j].take()).collect()
+    }
+    //бинарный турнир по рангам
+    fn tournament(rng: &mut dyn RngCore, ranks: &[(usize, f32)]) -> usize {
+        let a = rng.gen_range(0..ranks.len());
+        let b = rng.gen_range(0..ranks.len());
+        let (front_a, distance_a) = ranks[a];
+        let (front_b, distance_b) = ranks[b];
+        if front_a < front_b || (front_a == front_b && distance_a >= distance_b) { a } else { b }
+    }
+}
+
+impl SelectionMethod for Nsga2Selection {
+    fn select<'a, I>(&self, rng: &mut dyn RngCore, population: &'a [I]) -> (&'a I, &'a I)
+    where
+        I: Individual,
+    {
+        self.try_select(rng, population).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    fn try_select<'a, I>(&self, rng: &mut dyn RngCore, population: &'a [I]) -> Result<(&'a I, &'a I), Error>
+    where
+        I: Individual,
+    {
+        let (a, b) = self.try_select_indices(rng, population)?;
+        Ok((&population[a], &population[b]))
+    }
+    //ранги считаются по fitness_vector особей
+    fn try_select_indices<I>(&self, rng: &mut dyn RngCore, population: &[I]) -> Result<(usize, usize), Error>
+    where
+        I: Individual,
+    {
+        if population.is_empty() {
+            return Err(Error::EmptyPopulation);
+        }
+        if let Some(ranks) = self.ranks.borrow().as_ref().filter(|r| r.len() == population.len()) {
+            return Ok((Self::tournament(rng, ranks), Self::tournament(rng, ranks)));
+        }
+        //вызов вне evolve (или в begin_generation цели оказались разной длины) -
+        //ранги считаются только для этого выбора
+        let ranks = Self::try_ranks(population)?;
+        Ok((Self::tournament(rng, &ranks), Self::tournament(rng, &ranks)))
+    }
+
+    fn begin_generation<I>(&self, population: &[I])
+    where
+        I: Individual,
+    {
+        //при ошибке рангов нет, и ее вернет выбор родителей
+        *self.ranks.borrow_mut() = Self::try_ranks(population).ok();
+    }
+
+    fn end_generation(&self) {
+        self.ranks.borrow_mut().take();
+    }
+}
+
+/// a доминирует b: не хуже по всем целям и лучше хотя бы по одной (цели максимизируются)
+pub fn dominates(a: &[f32], b: &[f32]) -> bool {
+    a.iter().zip(b).all(|(x, y)| x >= y) && a.iter().zip(b).any(|(x, y)| x > y)
+}
+
+/// Быстрая недоминируемая сортировка: фронты (индексы особей), начиная с фронта Парето
+pub fn non_dominated_sort(objectives: &[Vec<f32>]) -> Vec<Vec<usize>> {
+    let len = objectives.len();
+    let mut dominated: Vec<Vec<usize>> = vec![Vec::new(); len];//кого доминирует особь
+    let mut domination_count: Vec<usize> = vec![0; len];//сколькими особь доминируется
+    let mut fronts: Vec<Vec<usize>> = vec![Vec::new()];
+    for p in 0..len {
+        for q in 0..len {
+            if dominates(&objectives[p], &objectives[q]) {
+                dominated[p].push(q);
+            } else if dominates(&objectives[q], &objectives[p]) {
+                domination_count[p] += 1;
+            }
+        }
+        if domination_count[p] == 0 {
+            fronts[0].push(p);
+        }
+    }
+    loop {
+        let mut next: Vec<usize> = Vec::new();
+        for p in fronts.last().unwrap() {
+            for q in &dominated[*p] {
+                domination_count[*q] -= 1;
+                if domination_count[*q] == 0 {
+                    next.push(*q);
+                }
+            }
+        }
+        if next.is_empty() { break; }
+        fronts.push(next);
+    }
+    fronts.retain(|front| !front.is_empty());
+    fronts
+}
+
+//цели особей, у всех одной длины
+fn objectives<I>(population: &[I]) -> Result<Vec<Vec<f32>>, Error>
+where
+    I: Individual,
+{
+    let objectives: Vec<Vec<f32>> = population.iter().map(|i| i.fitness_vector()).collect();
+    if let Some(index) = objectives.iter().position(|o| o.len() != objectives[0].len()) {
+        return Err(Error::InvalidFitness { index });
+    }
+    Ok(objectives)
+}
+
+/// Расстояние скученности особей фронта (в порядке front), у крайних - бесконечность.
+/// Цели всех особей должны быть одной длины
+#[allow(clippy::needless_range_loop)] // m - номер цели у всех особей фронта
+pub fn crowding_distance(objectives: &[Vec<f32>], front: &[usize]) -> Vec<f32> {
+    let mut distance = vec![0.0f32; front.len()];
+    if front.len() < 3 {
+        distance.iter_mut().for_each(|d| *d = f32::INFINITY);
+        return distance;
+    }
+    let objective_count = objectives[front[0]].len();
+    for m in 0..objective_count {
+        let mut order: Vec<usize> = (0..front.len()).collect();
+        order.sort_by(|a, b| {
+            objectives[front[*a]][m].partial_cmp(&objectives[front[*b]][m]).unwrap_or(Ordering::Equal)
+        });
+        let min = objectives[front[order[0]]][m];
+        let max = objectives[front[order[front.len() - 1]]][m];
+        distance[order[0]] = f32::INFINITY;
+        distance[order[front.len() - 1]] = f32::INFINITY;
+        if max - min <= 0.0 { continue; }
+        for j in 1..front.len() - 1 {
+            let next = objectives[front[order[j + 1]]][m];
+            let prev = objectives[front[order[j - 1]]][m];
+            distance[order[j]] += (next - prev) / (max - min);
+        }
+    }
+    distance
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn objectives() -> Vec<Vec<f32>> {
+        vec![
+            vec![1.0, 5.0],//фронт 0
+            vec![2.0, 4.0],//фронт 0
+            vec![1.0, 3.0],//фронт 1
+            vec![4.0, 1.0],//фронт 0
+            vec![0.5, 0.5],//фронт 2
+        ]
+    }
+
+    #[test]
+    fn sort() {
+        assert!(dominates(&[2.0, 4.0], &[1.0, 3.0]));
+        assert!(!dominates(&[2.0, 4.0], &[2.0, 4.0]));
+        assert!(!dominates(&[2.0, 4.0], &[4.0, 1.0]));
+        assert_eq!(non_dominated_sort(&objectives()), vec![vec![0, 1, 3], vec![2], vec![4]]);
+    }
+
+    #[test]
+    fn crowding() {
+        let distance = crowding_distance(&objectives(), &[0, 1, 3]);
+
+        assert_eq!(distance[0], f32::INFINITY);
+        approx::assert_relative_eq!(distance[1], 3.0 / 3.0 + 4.0 / 4.0);
+        assert_eq!(distance[2], f32::INFINITY);
+    }
+
+    #[test]
+    fn selects_the_pareto_front_more_often() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let population: Vec<TestIndividual> = objectives()
+            .iter()
+            .map(|objectives| TestIndividual::with_objectives(objectives))
+            .collect();
+
+        let ranks = Nsga2Selection::ranks(&population);
+        assert_eq!(ranks.iter().map(|(front, _)| *front).collect::<Vec<_>>(), vec![0, 0, 1, 0, 2]);
+
+        let selection = Nsga2Selection::new();
+        selection.begin_generation(&population);
+        let mut histogram = [0; 5];
+        for _ in 0..1000 {
+            let (a, b) = selection.select(&mut rng, &population);
+            for parent in [a, b] {
+                histogram[population.iter().position(|i| i == parent).unwrap()] += 1;
+            }
+        }
+        assert!(histogram[4] < histogram[2]);
+        assert!(histogram[2] < histogram[0].min(histogram[1]).min(histogram[3]));
+        selection.end_generation();
+        assert!(selection.ranks.borrow().is_none());
+    }
+
+    #[test]
+    fn objectives_of_different_length() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let population = vec![
+            TestIndividual::with_objectives(&[1.0, 5.0]),
+            TestIndividual::with_objectives(&[2.0, 4.0]),
+            TestIndividual::with_objectives(&[3.0]),
+            TestIndividual::with_objectives(&[4.0, 1.0]),
+        ];
+        let selection = Nsga2Selection::new();
+        selection.begin_generation(&population);
+
+        assert!(selection.ranks.borrow().is_none());
+        assert_eq!(selection.try_select_indices(&mut rng, &population), Err(Error::InvalidFitness { index: 2 }));
+        assert_eq!(Nsga2Selection::try_ranks(&population), Err(Error::InvalidFitness { index: 2 }));
+    }
+
+    #[test]
+    fn environmental_selection() {
+        let population: Vec<TestIndividual> = objectives()
+            .iter()
+            .map(|objectives| TestIndividual::with_objectives(objectives))
+            .collect();
+
+        //фронт 0 целиком и лучший из фронта 1
+        assert_eq!(Nsga2Selection::survivors(&population, 4), vec![0, 1, 3, 2]);
+        //во фронте 0 у средней особи скученность меньше - она отбрасывается
+        let survivors = Nsga2Selection::environmental_selection(population, 2);
+        assert_eq!(survivors.iter().map(|i| i.fitness_vector()).collect::<Vec<_>>(),
+                   vec![vec![1.0, 5.0], vec![4.0, 1.0]]);
+    }
+}
@@ -8,13 +8,52 @@ impl SelectionMethod for RouletteWheelSelection {
     where
         I: Individual,
     {
-        (population //вероятность выбора каждой особи пропорциональна ее пригодности (fitness())
-            .choose_weighted(rng, |individual| individual.fitness().max(0.00001))
-            .expect("got an empty population"),
-         population //вероятность выбора каждой особи пропорциональна ее пригодности (fitness())
-             .choose_weighted(rng, |individual| individual.fitness().max(0.00001))
-             .expect("got an empty population")
-        )
+        self.try_select(rng, population).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    fn try_select<'a, I>(&self, rng: &mut dyn RngCore, population: &'a [I]) -> Result<(&'a I, &'a I), Error>
+    where
+        I: Individual,
+    {
+        let (a, b) = self.try_select_indices(rng, population)?;
+        Ok((&population[a], &population[b]))
+    }
+
+    fn try_select_indices<I>(&self, rng: &mut dyn RngCore, population: &[I]) -> Result<(usize, usize), Error>
+    where
+        I: Individual,
+    {
+        let fitnesses: Vec<f32> = population.iter().map(|i| i.fitness()).collect();
+        self.try_select_by_fitness(rng, population, &fitnesses)
+    }
+}
+
+impl FitnessSelectionMethod for RouletteWheelSelection {
+    fn try_select_by_fitness<I>(
+        &self,
+        rng: &mut dyn RngCore,
+        population: &[I],
+        fitnesses: &[f32],
+    ) -> Result<(usize, usize), Error>
+    where
+        I: Individual,
+    {
+        if population.is_empty() {
+            return Err(Error::EmptyPopulation);
+        }
+        if fitnesses.len() != population.len() {
+            return Err(Error::InvalidParameter { name: "fitnesses", value: fitnesses.len() as f32 });
+        }
+        //вероятность выбора каждой особи пропорциональна ее пригодности
+        let weight = |j: &usize| fitnesses[*j].max(0.00001);
+        let indices: Vec<usize> = (0..population.len()).collect();
+        //сумма весов должна быть конечной
+        let mut total = 0.0f32;
+        if let Some(index) = indices.iter().position(|j| { total += weight(j); !total.is_finite() }) {
+            return Err(Error::InvalidFitness { index });
+        }
+        Ok((*indices.choose_weighted(rng, weight).map_err(|_| Error::EmptyPopulation)?,
+            *indices.choose_weighted(rng, weight).map_err(|_| Error::EmptyPopulation)?))
     }
 }
 
@@ -53,4 +92,15 @@ mod test {
 
         assert_eq!(actual_histogram, expected_histogram);
     }
+
+    #[test]
+    fn errors() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let empty: Vec<TestIndividual> = Vec::new();
+        let infinite = vec![TestIndividual::new(1.0), TestIndividual::new(f32::INFINITY)];
+
+        assert_eq!(RouletteWheelSelection.try_select(&mut rng, &empty), Err(Error::EmptyPopulation));
+        assert_eq!(RouletteWheelSelection.try_select(&mut rng, &infinite),
+                   Err(Error::InvalidFitness { index: 1 }));
+    }
 }
@@ -0,0 +1,145 @@
+use crate::*;
+use std::cell::RefCell;
+//Селекция по масштабированному fitness: любая SelectionMethod получает вместо
+//fitness() особей результат FitnessScaling. Масштабированный fitness считается
+//один раз на поколение в begin_generation и сбрасывается в end_generation,
+//вне поколения - при каждом выборе.
+#[derive(Clone, Debug)]
+pub struct ScaledSelection<S, F> {
+    selection_method: S,
+    scaling: F,
+    scaled: RefCell<Option<Vec<f32>>>,//масштабированный fitness текущего поколения
+}
+
+impl<S, F> ScaledSelection<S, F>
+where
+    S: FitnessSelectionMethod,
+    F: FitnessScaling,
+{
+    pub fn new(selection_method: S, scaling: F) -> Self {
+        Self {
+            selection_method,
+            scaling,
+            scaled: RefCell::new(None),
+        }
+    }
+}
+
+impl<S, F> SelectionMethod for ScaledSelection<S, F>
+where
+    S: FitnessSelectionMethod,
+    F: FitnessScaling,
+{
+    fn select<'a, I>(&self, rng: &mut dyn RngCore, population: &'a [I]) -> (&'a I, &'a I)
+    where
+        I: Individual,
+    {
+        self.try_select(rng, population).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    fn try_select<'a, I>(&self, rng: &mut dyn RngCore, population: &'a [I]) -> Result<(&'a I, &'a I), Error>
+    where
+        I: Individual,
+    {
+        let (a, b) = self.try_select_indices(rng, population)?;
+        Ok((&population[a], &population[b]))
+    }
+
+    fn try_select_indices<I>(&self, rng: &mut dyn RngCore, population: &[I]) -> Result<(usize, usize), Error>
+    where
+        I: Individual,
+    {
+        if let Some(scaled) = self.scaled.borrow().as_ref() {
+            return self.selection_method.try_select_by_fitness(rng, population, scaled);
+        }
+        let fitnesses: Vec<f32> = population.iter().map(|i| i.fitness()).collect();
+        self.try_select_by_fitness(rng, population, &fitnesses)
+    }
+
+    fn begin_generation<I>(&self, population: &[I])
+    where
+        I: Individual,
+    {
+        let fitnesses: Vec<f32> = population.iter().map(|i| i.fitness()).collect();
+        self.scaling.observe(&fitnesses);
+        *self.scaled.borrow_mut() = Some(self.scaling.scale(&fitnesses));
+        self.selection_method.begin_generation(population);
+    }
+
+    fn end_generation(&self) {
+        self.scaled.borrow_mut().take();
+        self.selection_method.end_generation();
+    }
+}
+
+impl<S, F> FitnessSelectionMethod for ScaledSelection<S, F>
+where
+    S: FitnessSelectionMethod,
+    F: FitnessScaling,
+{
+    fn try_select_by_fitness<I>(
+        &self,
+        rng: &mut dyn RngCore,
+        population: &[I],
+        fitnesses: &[f32],
+    ) -> Result<(usize, usize), Error>
+    where
+        I: Individual,
+    {
+        self.selection_method.try_select_by_fitness(rng, population, &self.scaling.scale(fitnesses))
+    }
+}
+
+//Выбор родителей методом selection_method по fitness scaled вместо fitness() особей
+pub(crate) fn select_scaled<'a, S, I>(
+    selection_method: &S,
+    rng: &mut dyn RngCore,
+    population: &'a [I],
+    scaled: &[f32],
+) -> Result<(&'a I, &'a I), Error>
+where
+    S: FitnessSelectionMethod,
+    I: Individual,
+{
+    let (a, b) = selection_method.try_select_by_fitness(rng, population, scaled)?;
+    Ok((&population[a], &population[b]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+        //сырой fitness отрицательный - рулетка выбирала бы всех одинаково
+        let population = vec![
+            TestIndividual::new(-4.0),
+            TestIndividual::new(-3.0),
+            TestIndividual::new(-2.0),
+            TestIndividual::new(-1.0),
+        ];
+        let selection = ScaledSelection::new(RouletteWheelSelection, RankScaling);
+        selection.begin_generation(&population);
+
+        let mut histogram = [0; 4];
+        for _ in 0..1000 {
+            let (a, _) = selection.select(&mut rng, &population);
+            histogram[(a.fitness() + 4.0) as usize] += 1;
+        }
+        //вероятность пропорциональна рангу: 1:2:3:4
+        assert!(histogram.windows(2).all(|w| w[0] < w[1]));
+        assert!((80..120).contains(&histogram[0]));
+        assert!((360..440).contains(&histogram[3]));
+
+        //после поколения кэш сброшен, а fitness другой популяции масштабируется заново
+        selection.end_generation();
+        assert!(selection.scaled.borrow().is_none());
+        let population = vec![TestIndividual::new(-1.0), TestIndividual::new(-2.0)];
+        let (a, b) = selection.try_select_indices(&mut rng, &population).unwrap();
+        assert!(a < 2 && b < 2);
+    }
+}
@@ -6,9 +6,23 @@ pub struct Statistics {
     max_fitness: f32,
     avg_fitness: f32,
     median_fitness: f32,
+    std_dev_fitness: f32,//стандартное отклонение fitness
+    fitnesses: Vec<f32>,//fitness популяции по возрастанию, для квантилей
+    best_index: usize,//индекс лучшей особи в популяции
+    worst_index: usize,//индекс худшей особи в популяции
     changed_count: usize,//сколько I поменялось
-    neurons_by_layer_all: HashSet<String>,//сколько слоев и нейронов в каждом слое
+    topologies: HashMap<Vec<usize>, usize>,//кол. нейронов по слоям -> сколько особей
+    min_layer_count: usize,
+    max_layer_count: usize,
+    avg_layer_count: f32,
+    min_neuron_count: usize,
+    max_neuron_count: usize,
+    avg_neuron_count: f32,
+    avg_connection_count: f32,//связи между нейронами (без смещений и входов 1-го слоя)
     max_neuron_num: usize,//номер максимального нейрона в популяции
+    weight_diversity: Option<f32>,//ср. попарное расстояние весов по общим генам
+    topology_entropy: f32,//энтропия Шеннона частот топологий
+    phenotype_diversity: Option<f32>,//ср. попарное расстояние векторов поведения
 }
 
 impl Statistics {
@@ -37,36 +51,34 @@ impl Statistics {
             fitnesses[len / 2]
         };
 
-        //структура сети
-        let mut neurons_by_layer_all: HashSet<String> = HashSet::new();
+        let std_dev_fitness = (fitnesses.iter()
+            .map(|f| (f - avg_fitness).powi(2))
+            .sum::<f32>() / (len as f32))
+            .sqrt();
+
+        //индексы лучшей и худшей особи (первые из равных)
+        let mut best_index: usize = 0;
+        let mut worst_index: usize = 0;
+        for (j, individual) in population.iter().enumerate() {
+            let fitness = individual.fitness();
+            if fitness > population[best_index].fitness() { best_index = j; }
+            if fitness < population[worst_index].fitness() { worst_index = j; }
+        }
+
+        //структура сети: сколько особей имеют каждую топологию (кол. нейронов по слоям)
+        let mut topologies: HashMap<Vec<usize>, usize> = HashMap::new();
+        let mut layer_counts: Vec<usize> = Vec::with_capacity(len);
+        let mut neuron_counts: Vec<usize> = Vec::with_capacity(len);
+        let mut connection_count: usize = 0;
         for child in population {
-            let mut neurons_by_layer: Vec<Vec<usize>> = Vec::new();//нейроны послойно
-            for (w1,layer_num,n_out,n_in) in child.chromosome().iter() {
-                //добавляем новый слой
-                if neurons_by_layer.len() < layer_num {
-                    neurons_by_layer.resize_with(layer_num, Vec::new);
-                }
-                //ссылка на слой
-                let layer = &mut neurons_by_layer[layer_num - 1];
-                if layer_num == 1 {
-                    //для 1 слоя смотрим только n_in != 0 (не смещение),
-                    //добавляем вход только если вес > 0, т.е. не удален
-                    if n_in != 0 && w1 > 0.0 && !layer.contains(&n_out) {
-                        layer.push(n_out);
-                    }
-                } else {
-                    //для последующих слоев просто добавляем нейроны в слой (даже смещение)
-                    if !layer.contains(&n_out) {
-                        layer.push(n_out);
-                    }
-                }
-            }
-            let mut str1: String = "".to_string();
-            for nl in neurons_by_layer {
-                str1 = str1.add(nl.len().to_string().as_str());
-                str1 = str1.add(".");
-            }
-            neurons_by_layer_all.insert(str1);
+            let topology = Self::topology(child.chromosome());
+            layer_counts.push(topology.len());
+            neuron_counts.push(topology.iter().sum());
+            connection_count += child.chromosome()
+                .iter()
+                .filter(|&(_, layer_num, _, n_in)| layer_num > 1 && n_in != 0)
+                .count();
+            *topologies.entry(topology).or_default() += 1;
         }
 
         //максимальный номер нейрона по популяции
@@ -81,16 +93,62 @@ impl Statistics {
             if max_n > max_n_out { max_n_out = max_n };
         }
 
+        //энтропия частот топологий
+        let topology_entropy = -topologies.values()
+            .map(|count| *count as f32 / len as f32)
+            .map(|p| p * p.ln())
+            .sum::<f32>();
+
         Self {
             min_fitness,
             max_fitness,
             avg_fitness,
             median_fitness,
+            std_dev_fitness,
+            fitnesses,
+            best_index,
+            worst_index,
             changed_count: 0,
-            neurons_by_layer_all,
+            topologies,
+            min_layer_count: layer_counts.iter().copied().min().unwrap_or(0),
+            max_layer_count: layer_counts.iter().copied().max().unwrap_or(0),
+            avg_layer_count: layer_counts.iter().sum::<usize>() as f32 / (len as f32),
+            min_neuron_count: neuron_counts.iter().copied().min().unwrap_or(0),
+            max_neuron_count: neuron_counts.iter().copied().max().unwrap_or(0),
+            avg_neuron_count: neuron_counts.iter().sum::<usize>() as f32 / (len as f32),
+            avg_connection_count: connection_count as f32 / (len as f32),
             max_neuron_num: max_n_out,
+            weight_diversity: None,
+            topology_entropy,
+            phenotype_diversity: None,
         }
     }
+    //кол. нейронов по слоям сети особи
+    fn topology(chromosome: &Chromosome) -> Vec<usize> {
+        let mut neurons_by_layer: Vec<Vec<usize>> = Vec::new();//нейроны послойно
+        for (w1,layer_num,n_out,n_in) in chromosome.iter() {
+            if layer_num == 0 { continue; }//у генов без топологии сети нет слоя
+            //добавляем новый слой
+            if neurons_by_layer.len() < layer_num {
+                neurons_by_layer.resize_with(layer_num, Vec::new);
+            }
+            //ссылка на слой
+            let layer = &mut neurons_by_layer[layer_num - 1];
+            if layer_num == 1 {
+                //для 1 слоя смотрим только n_in != 0 (не смещение),
+                //добавляем вход только если вес > 0, т.е. не удален
+                if n_in != 0 && w1 > 0.0 && !layer.contains(&n_out) {
+                    layer.push(n_out);
+                }
+            } else {
+                //для последующих слоев просто добавляем нейроны в слой (даже смещение)
+                if !layer.contains(&n_out) {
+                    layer.push(n_out);
+                }
+            }
+        }
+        neurons_by_layer.iter().map(|layer| layer.len()).collect()
+    }
 
     pub fn min_fitness(&self) -> f32 {
         self.min_fitness
@@ -108,6 +166,41 @@ impl Statistics {
         self.median_fitness
     }
 
+    pub fn std_dev_fitness(&self) -> f32 {
+        self.std_dev_fitness
+    }
+    /// Квантиль fitness популяции (q от 0.0 до 1.0), с линейной интерполяцией
+    pub fn quantile_fitness(&self, q: f32) -> f32 {
+        let pos = q.clamp(0.0, 1.0) * (self.fitnesses.len() - 1) as f32;
+        let lo = pos.floor() as usize;
+        let hi = pos.ceil() as usize;
+        self.fitnesses[lo] + (self.fitnesses[hi] - self.fitnesses[lo]) * (pos - lo as f32)
+    }
+
+    pub fn p10_fitness(&self) -> f32 {
+        self.quantile_fitness(0.10)
+    }
+
+    pub fn p25_fitness(&self) -> f32 {
+        self.quantile_fitness(0.25)
+    }
+
+    pub fn p75_fitness(&self) -> f32 {
+        self.quantile_fitness(0.75)
+    }
+
+    pub fn p90_fitness(&self) -> f32 {
+        self.quantile_fitness(0.90)
+    }
+    /// Индекс лучшей особи в оцениваемой популяции
+    pub fn best_index(&self) -> usize {
+        self.best_index
+    }
+    /// Индекс худшей особи в оцениваемой популяции
+    pub fn worst_index(&self) -> usize {
+        self.worst_index
+    }
+
     pub fn changed_count(&self) -> usize {
         self.changed_count
     }
@@ -115,9 +208,179 @@ impl Statistics {
         self.changed_count = ch;
     }
 
-    pub fn neurons_by_layer(&self) -> HashSet<String> { self.neurons_by_layer_all.clone() }
+    /// Топологии популяции в виде строк "3.4.2." (кол. нейронов по слоям)
+    pub fn neurons_by_layer(&self) -> HashSet<String> {
+        self.topologies
+            .keys()
+            .map(|topology| topology.iter().map(|n| format!("{n}.")).collect())
+            .collect()
+    }
+    /// Топологии популяции: кол. нейронов по слоям -> сколько особей имеют такую топологию
+    pub fn topologies(&self) -> &HashMap<Vec<usize>, usize> {
+        &self.topologies
+    }
+
+    pub fn min_layer_count(&self) -> usize { self.min_layer_count }
+
+    pub fn max_layer_count(&self) -> usize { self.max_layer_count }
+
+    pub fn avg_layer_count(&self) -> f32 { self.avg_layer_count }
+
+    pub fn min_neuron_count(&self) -> usize { self.min_neuron_count }
+
+    pub fn max_neuron_count(&self) -> usize { self.max_neuron_count }
+
+    pub fn avg_neuron_count(&self) -> f32 { self.avg_neuron_count }
+
+    pub fn avg_connection_count(&self) -> f32 { self.avg_connection_count }
 
     pub fn max_neuron_num(&self) -> usize { self.max_neuron_num }
+    /// Разнообразие генотипа, если оно было посчитано через set_weight_diversity
+    pub fn weight_diversity(&self) -> Option<f32> { self.weight_diversity }
+    /// Кол. различных топологий в популяции
+    pub fn topology_count(&self) -> usize { self.topologies.len() }
+    /// Энтропия Шеннона (в натах) частот топологий, 0 - у всех особей одна топология
+    pub fn topology_entropy(&self) -> f32 { self.topology_entropy }
+    /// Разнообразие фенотипа, если оно было задано через set_phenotype_diversity
+    pub fn phenotype_diversity(&self) -> Option<f32> { self.phenotype_diversity }
+    /// Разнообразие генотипа: ср. попарное расстояние весов (ср. модуль разности) по генам,
+    /// общим для пары особей. Считается O(N^2 * кол. генов), поэтому не входит в new и
+    /// вызывается отдельно для той же популяции, когда нужно
+    pub fn set_weight_diversity<I>(&mut self, population: &[I])
+    where
+        I: Individual,
+    {
+        let genes: Vec<HashMap<(usize, usize, usize), f32>> = population.iter()
+            .map(|child| child.chromosome().iter().map(|(w, l, o, i)| ((l, o, i), w)).collect())
+            .collect();
+        let mut distance_sum: f32 = 0.0;
+        let mut pair_count: usize = 0;
+        for (j, genes_a) in genes.iter().enumerate() {
+            for genes_b in &genes[j + 1..] {
+                let (sum, shared) = genes_a.iter()
+                    .filter_map(|(key, w_a)| genes_b.get(key).map(|w_b| (w_a - w_b).abs()))
+                    .fold((0.0f32, 0usize), |(sum, n), d| (sum + d, n + 1));
+                if shared > 0 {//у пары нет общих генов - не учитываем
+                    distance_sum += sum / shared as f32;
+                    pair_count += 1;
+                }
+            }
+        }
+        self.weight_diversity =
+            Some(if pair_count > 0 { distance_sum / pair_count as f32 } else { 0.0 });
+    }
+    /// Разнообразие фенотипа по векторам поведения особей (задаются пользователем, например
+    /// конечная позиция птички): ср. попарное евклидово расстояние.
+    /// Ошибка, если длина вектора поведения отличается от первого
+    pub fn set_phenotype_diversity(&mut self, behaviours: &[Vec<f32>]) -> Result<(), Error> {
+        let mut distance_sum: f32 = 0.0;
+        let mut pair_count: usize = 0;
+        for (j, a) in behaviours.iter().enumerate() {
+            for (k, b) in behaviours.iter().enumerate().skip(j + 1) {
+                distance_sum += euclidean_distance(a, b).ok_or(Error::InvalidBehaviour { index: k })?;
+                pair_count += 1;
+            }
+        }
+        self.phenotype_diversity =
+            Some(if pair_count > 0 { distance_sum / pair_count as f32 } else { 0.0 });
+        Ok(())
+    }
+}
+
+/// Статистика многокритериальной популяции (Individual::fitness_vector)
+#[derive(Clone, Debug)]
+pub struct ParetoStatistics {
+    objective_count: usize,//кол. целей
+    front_size: usize,//кол. особей во фронте Парето
+    hypervolume: Option<f32>,//гиперобъем фронта, для 2-3 целей
+}
+
+impl ParetoStatistics {
+    /// Статистика по популяции. reference - опорная точка гиперобъема, хуже всех особей
+    /// по каждой цели (цели максимизируются)
+    pub fn new<I>(population: &[I], reference: &[f32]) -> Self
+    where
+        I: Individual,
+    {
+        Self::try_new(population, reference).unwrap_or_else(|err| panic!("{err}"))
+    }
+    /// new без паники: популяция не должна быть пустой
+    pub fn try_new<I>(population: &[I], reference: &[f32]) -> Result<Self, Error>
+    where
+        I: Individual,
+    {
+        if population.is_empty() {
+            return Err(Error::EmptyPopulation);
+        }
+        let objectives: Vec<Vec<f32>> = population.iter().map(|i| i.fitness_vector()).collect();
+        let objective_count = objectives[0].len();
+        let front: Vec<Vec<f32>> = non_dominated_sort(&objectives)[0]
+            .iter()
+            .map(|j| objectives[*j].clone())
+            .collect();
+        let hypervolume = match objective_count {
+            2 | 3 if reference.len() == objective_count => Some(hypervolume(&front, reference)),
+            _ => None,
+        };
+        Ok(Self {
+            objective_count,
+            front_size: front.len(),
+            hypervolume,
+        })
+    }
+
+    pub fn objective_count(&self) -> usize {
+        self.objective_count
+    }
+
+    pub fn front_size(&self) -> usize {
+        self.front_size
+    }
+    /// Гиперобъем фронта Парето относительно опорной точки (только для 2-3 целей)
+    pub fn hypervolume(&self) -> Option<f32> {
+        self.hypervolume
+    }
+}
+
+//гиперобъем точек над опорной точкой (2 или 3 цели, максимизация)
+fn hypervolume(points: &[Vec<f32>], reference: &[f32]) -> f32 {
+    //учитываем только точки лучше опорной по всем целям
+    let mut points: Vec<&Vec<f32>> = points.iter()
+        .filter(|p| p.iter().zip(reference).all(|(x, r)| x > r))
+        .collect();
+    if reference.len() == 2 {
+        //площадь ступенек: по убыванию 1-й цели
+        points.sort_by(|a, b| b[0].partial_cmp(&a[0]).unwrap_or(Ordering::Equal));
+        let mut area = 0.0;
+        let mut top = reference[1];
+        for p in points {
+            if p[1] > top {
+                area += (p[0] - reference[0]) * (p[1] - top);
+                top = p[1];
+            }
+        }
+        area
+    } else {
+        //срезы по 3-й цели: по убыванию, площадь 2D фронта точек выше среза
+        points.sort_by(|a, b| b[2].partial_cmp(&a[2]).unwrap_or(Ordering::Equal));
+        let mut volume = 0.0;
+        for (j, p) in points.iter().enumerate() {
+            let next = points.get(j + 1).map_or(reference[2], |n| n[2]);
+            if p[2] > next {
+                let slice: Vec<Vec<f32>> = points[..=j].iter().map(|q| q[..2].to_vec()).collect();
+                volume += hypervolume(&slice, &reference[..2]) * (p[2] - next);
+            }
+        }
+        volume
+    }
+}
+
+//евклидово расстояние между векторами поведения, None - у векторов разная длина
+pub(crate) fn euclidean_distance(a: &[f32], b: &[f32]) -> Option<f32> {
+    if a.len() != b.len() {
+        return None;
+    }
+    Some(a.iter().zip(b).map(|(x, y)| (x - y).powi(2)).sum::<f32>().sqrt())
 }
 
 #[cfg(test)]
@@ -137,6 +400,13 @@ mod tests {
         approx::assert_relative_eq!(stats.max_fitness(), 40.0);
         approx::assert_relative_eq!(stats.avg_fitness(), (10.0 + 20.0 + 30.0 + 40.0) / 4.0);
         approx::assert_relative_eq!(stats.median_fitness(), (20.0 + 30.0) / 2.0);
+        approx::assert_relative_eq!(stats.std_dev_fitness(), 125.0f32.sqrt());
+        approx::assert_relative_eq!(stats.p25_fitness(), 17.5);
+        approx::assert_relative_eq!(stats.p75_fitness(), 32.5);
+        approx::assert_relative_eq!(stats.quantile_fitness(0.0), 10.0);
+        approx::assert_relative_eq!(stats.quantile_fitness(1.0), 40.0);
+        assert_eq!(stats.best_index(), 3);
+        assert_eq!(stats.worst_index(), 1);
     }
 
     #[test]
@@ -151,5 +421,94 @@ mod tests {
         approx::assert_relative_eq!(stats.max_fitness(), 40.0);
         approx::assert_relative_eq!(stats.avg_fitness(), (20.0 + 30.0 + 40.0) / 3.0);
         approx::assert_relative_eq!(stats.median_fitness(), 30.0);
+        approx::assert_relative_eq!(stats.quantile_fitness(0.5), 30.0);
+        approx::assert_relative_eq!(stats.p10_fitness(), 22.0);
+        approx::assert_relative_eq!(stats.p90_fitness(), 38.0);
+        assert_eq!(stats.best_index(), 2);
+        assert_eq!(stats.worst_index(), 1);
+    }
+
+    #[test]
+    fn test_topology() {
+        let net = |hidden: &[usize]| {
+            let mut genes = vec![(0.0,1,1,0), (1.0,1,1,1), (0.0,1,2,0), (1.0,1,2,2)];
+            for n in hidden {
+                genes.extend([(0.1,2,*n,0), (0.2,2,*n,1), (0.3,2,*n,2)]);
+            }
+            genes.push((0.4,3,9,0));
+            genes.extend(hidden.iter().map(|n| (0.5,3,9,*n)));
+            TestIndividual::create(genes.into_iter().collect())
+        };
+        let stats = Statistics::new(&[net(&[3]), net(&[3, 4]), net(&[3, 5])]);
+
+        assert_eq!(stats.topologies(), &maplit::hashmap! {
+            vec![2, 1, 1] => 1,
+            vec![2, 2, 1] => 2,
+        });
+        assert_eq!(stats.neurons_by_layer(),
+                   maplit::hashset! { "2.1.1.".to_string(), "2.2.1.".to_string() });
+        assert_eq!(stats.min_layer_count(), 3);
+        assert_eq!(stats.max_layer_count(), 3);
+        assert_eq!(stats.min_neuron_count(), 4);
+        assert_eq!(stats.max_neuron_count(), 5);
+        approx::assert_relative_eq!(stats.avg_neuron_count(), 14.0 / 3.0);
+        approx::assert_relative_eq!(stats.avg_connection_count(), (3.0 + 6.0 + 6.0) / 3.0);
+        assert_eq!(stats.max_neuron_num(), 9);
+        assert_eq!(stats.topology_count(), 2);
+        approx::assert_relative_eq!(stats.topology_entropy(),
+                                    -(1.0f32 / 3.0 * (1.0f32 / 3.0).ln() + 2.0 / 3.0 * (2.0f32 / 3.0).ln()));
+    }
+
+    #[test]
+    fn test_diversity() {
+        let mut stats = Statistics::new(&[
+            TestIndividual::create(vec![(0.0,1,1,0), (1.0,2,2,0)].into_iter().collect()),
+            TestIndividual::create(vec![(0.5,1,1,0), (2.0,2,2,0)].into_iter().collect()),
+            TestIndividual::create(vec![(0.0,1,1,0), (3.0,2,3,0)].into_iter().collect()),
+        ]);
+
+        assert_eq!(stats.weight_diversity(), None);
+        stats.set_weight_diversity(&[
+            TestIndividual::create(vec![(0.0,1,1,0), (1.0,2,2,0)].into_iter().collect()),
+            TestIndividual::create(vec![(0.5,1,1,0), (2.0,2,2,0)].into_iter().collect()),
+            TestIndividual::create(vec![(0.0,1,1,0), (3.0,2,3,0)].into_iter().collect()),
+        ]);
+        //пары: (0,1) - 0.75, (0,2) - 0.0, (1,2) - 0.5
+        approx::assert_relative_eq!(stats.weight_diversity().unwrap(), 1.25 / 3.0);
+        assert_eq!(stats.topology_count(), 1);
+        approx::assert_relative_eq!(stats.topology_entropy(), 0.0);
+
+        assert_eq!(stats.phenotype_diversity(), None);
+        stats.set_phenotype_diversity(&[vec![0.0, 0.0], vec![3.0, 4.0], vec![0.0, 0.0]]).unwrap();
+        approx::assert_relative_eq!(stats.phenotype_diversity().unwrap(), 10.0 / 3.0);
+        assert_eq!(stats.set_phenotype_diversity(&[vec![0.0, 0.0], vec![3.0]]),
+                   Err(Error::InvalidBehaviour { index: 1 }));
+    }
+
+    #[test]
+    fn test_pareto() {
+        let stats = ParetoStatistics::new(&[
+            TestIndividual::with_objectives(&[1.0, 3.0]),
+            TestIndividual::with_objectives(&[2.0, 2.0]),
+            TestIndividual::with_objectives(&[3.0, 1.0]),
+            TestIndividual::with_objectives(&[1.0, 1.0]),
+        ], &[0.0, 0.0]);
+
+        assert_eq!(stats.objective_count(), 2);
+        assert_eq!(stats.front_size(), 3);
+        approx::assert_relative_eq!(stats.hypervolume().unwrap(), 3.0 + 2.0 + 1.0);
+
+        let stats = ParetoStatistics::new(&[
+            TestIndividual::with_objectives(&[1.0, 1.0, 2.0]),
+            TestIndividual::with_objectives(&[2.0, 2.0, 1.0]),
+        ], &[0.0, 0.0, 0.0]);
+
+        assert_eq!(stats.front_size(), 2);
+        approx::assert_relative_eq!(stats.hypervolume().unwrap(), 1.0 * 1.0 + 4.0 * 1.0);
+
+        let stats = ParetoStatistics::new(&[TestIndividual::new(1.0)], &[0.0]);
+        assert_eq!(stats.hypervolume(), None);
+        let empty: Vec<TestIndividual> = Vec::new();
+        assert_eq!(ParetoStatistics::try_new(&empty, &[0.0]).err(), Some(Error::EmptyPopulation));
     }
 }
@@ -0,0 +1,210 @@
+use crate::*;
+use std::io::{self, Write};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Статистика одного поколения в истории обучения
+#[derive(Clone, Debug)]
+pub struct StatisticsRecord {
+    generation: usize,//номер поколения
+    timestamp: SystemTime,//время записи
+    statistics: Statistics,
+}
+
+impl StatisticsRecord {
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    pub fn timestamp(&self) -> SystemTime {
+        self.timestamp
+    }
+
+    pub fn statistics(&self) -> &Statistics {
+        &self.statistics
+    }
+}
+
+/// История статистики по поколениям, с выводом в CSV или JSON Lines.
+/// Позволяет сравнивать запуски обучения между собой.
+#[derive(Clone, Debug, Default)]
+pub struct StatisticsHistory {
+    records: Vec<StatisticsRecord>,
+    best_fitness: Option<(f32, usize)>,//(лучший fitness за все время, поколение)
+}
+
+impl StatisticsHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Добавление статистики следующего поколения (нумерация с 0)
+    pub fn record(&mut self, statistics: Statistics) {
+        let generation = self.records.last().map_or(0, |r| r.generation + 1);
+        self.record_generation(generation, statistics);
+    }
+    /// Добавление статистики поколения с явным номером
+    pub fn record_generation(&mut self, generation: usize, statistics: Statistics) {
+        let max_fitness = statistics.max_fitness();
+        if self.best_fitness.map_or(true, |(best, _)| max_fitness > best) {
+            self.best_fitness = Some((max_fitness, generation));
+        }
+        self.records.push(StatisticsRecord {
+            generation,
+            timestamp: SystemTime::now(),
+            statistics,
+        });
+    }
+
+    pub fn records(&self) -> &[StatisticsRecord] {
+        &self.records
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+    /// Лучший fitness за все время обучения
+    pub fn best_fitness(&self) -> Option<f32> {
+        self.best_fitness.map(|(fitness, _)| fitness)
+    }
+    /// Поколение, в котором был достигнут лучший fitness
+    pub fn best_generation(&self) -> Option<usize> {
+        self.best_fitness.map(|(_, generation)| generation)
+    }
+    /// Время от первой до последней записи
+    pub fn elapsed(&self) -> Duration {
+        match (self.records.first(), self.records.last()) {
+            (Some(first), Some(last)) =>
+                last.timestamp.duration_since(first.timestamp).unwrap_or_default(),
+            _ => Duration::ZERO,
+        }
+    }
+    /// Вывод истории в CSV, первая строка - заголовок
+    pub fn write_csv<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let header: Vec<&str> = Self::columns(None).into_iter().map(|(name, _)| name).collect();
+        writeln!(writer, "{}", header.join(","))?;
+        for record in &self.records {
+            let values: Vec<String> = Self::columns(Some(record))
+                .into_iter()
+                .map(|(_, value)| match value {
+                    Value::Int(v) => v.to_string(),
+                    Value::Float(v) => v.to_string(),
+                    Value::None => String::new(),
+                })
+                .collect();
+            writeln!(writer, "{}", values.join(","))?;
+        }
+        Ok(())
+    }
+    /// Вывод истории в JSON Lines, по одному объекту на поколение
+    pub fn write_json_lines<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        for record in &self.records {
+            let fields: Vec<String> = Self::columns(Some(record))
+                .into_iter()
+                .map(|(name, value)| match value {
+                    Value::Int(v) => format!("\"{name}\":{v}"),
+                    Value::Float(v) if v.is_finite() => format!("\"{name}\":{v}"),
+                    _ => format!("\"{name}\":null"),//в JSON нет NaN и inf
+                })
+                .collect();
+            writeln!(writer, "{{{}}}", fields.join(","))?;
+        }
+        Ok(())
+    }
+    //колонки вывода: (имя, значение). Без записи - только имена
+    fn columns(record: Option<&StatisticsRecord>) -> Vec<(&'static str, Value)> {
+        let int = |f: fn(&StatisticsRecord) -> u128| record.map_or(Value::None, |r| Value::Int(f(r)));
+        let float = |f: fn(&Statistics) -> f32| {
+            record.map_or(Value::None, |r| Value::Float(f(&r.statistics)))
+        };
+        vec![
+            ("generation", int(|r| r.generation as u128)),
+            ("timestamp_ms", int(|r| {
+                r.timestamp.duration_since(UNIX_EPOCH).unwrap_or_default().as_millis()
+            })),
+            ("min_fitness", float(Statistics::min_fitness)),
+            ("max_fitness", float(Statistics::max_fitness)),
+            ("avg_fitness", float(Statistics::avg_fitness)),
+            ("median_fitness", float(Statistics::median_fitness)),
+            ("std_dev_fitness", float(Statistics::std_dev_fitness)),
+            ("p10_fitness", float(Statistics::p10_fitness)),
+            ("p90_fitness", float(Statistics::p90_fitness)),
+            ("best_index", int(|r| r.statistics.best_index() as u128)),
+            ("changed_count", int(|r| r.statistics.changed_count() as u128)),
+            ("topology_count", int(|r| r.statistics.topology_count() as u128)),
+            ("topology_entropy", float(Statistics::topology_entropy)),
+            ("weight_diversity", record
+                .and_then(|r| r.statistics.weight_diversity())
+                .map_or(Value::None, Value::Float)),
+            ("phenotype_diversity", record
+                .and_then(|r| r.statistics.phenotype_diversity())
+                .map_or(Value::None, Value::Float)),
+            ("avg_layer_count", float(Statistics::avg_layer_count)),
+            ("avg_neuron_count", float(Statistics::avg_neuron_count)),
+            ("avg_connection_count", float(Statistics::avg_connection_count)),
+            ("max_neuron_num", int(|r| r.statistics.max_neuron_num() as u128)),
+        ]
+    }
+}
+
+//значение колонки вывода
+enum Value {
+    Int(u128),
+    Float(f32),
+    None,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history() -> StatisticsHistory {
+        let mut history = StatisticsHistory::new();
+        history.record(Statistics::new(&[TestIndividual::new(1.0), TestIndividual::new(3.0)]));
+        history.record(Statistics::new(&[TestIndividual::new(2.0), TestIndividual::new(5.0)]));
+        history.record(Statistics::new(&[TestIndividual::new(4.0), TestIndividual::new(4.5)]));
+        history
+    }
+
+    #[test]
+    fn best_ever() {
+        let history = history();
+
+        assert_eq!(history.len(), 3);
+        assert_eq!(history.records()[2].generation(), 2);
+        assert_eq!(history.best_fitness(), Some(5.0));
+        assert_eq!(history.best_generation(), Some(1));
+        assert_eq!(StatisticsHistory::new().best_fitness(), None);
+    }
+
+    #[test]
+    fn csv() {
+        let mut out = Vec::new();
+        history().write_csv(&mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].starts_with("generation,timestamp_ms,min_fitness,max_fitness,avg_fitness,"));
+        let row: Vec<&str> = lines[2].split(',').collect();
+        assert_eq!(row.len(), lines[0].split(',').count());
+        assert_eq!((row[0], row[2], row[3], row[4]), ("1", "2", "5", "3.5"));
+        assert_eq!(row[14], "");//phenotype_diversity не задано
+    }
+
+    #[test]
+    fn json_lines() {
+        let mut out = Vec::new();
+        history().write_json_lines(&mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+
+        assert_eq!(lines.len(), 3);
+        assert!(lines[1].starts_with("{\"generation\":1,\"timestamp_ms\":"));
+        assert!(lines[1].contains("\"max_fitness\":5,"));
+        assert!(lines[1].contains("\"phenotype_diversity\":null,"));
+        assert!(lines[1].ends_with('}'));
+    }
+}